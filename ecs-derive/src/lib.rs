@@ -0,0 +1,264 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{ quote, format_ident };
+use syn::{ parse_macro_input, Data, DeriveInput, Error, Field, Fields, Ident, Type };
+
+
+/// Derives the ecs::Component trait for a struct, using its 'active: bool' field
+/// to tell if the component is active.
+///
+/// ```ignore
+/// #[derive(Default, Clone, Component)]
+/// pub struct Health {
+///     active: bool,
+///     pub current_hp: u32,
+/// }
+/// ```
+///
+#[proc_macro_derive(Component)]
+pub fn derive_component(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match component(&input) {
+        Ok(output) => output.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn component(input: &DeriveInput) -> Result<proc_macro2::TokenStream, Error> {
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = named_fields(input, "Component")?;
+    let Some(active) = fields.iter().find(|field| field.ident.as_ref().is_some_and(|ident| ident == "active")) else {
+        return Err(Error::new_spanned(name, "deriving Component requires a field 'active: bool'"));
+    };
+    if !is_bool(&active.ty) {
+        return Err(Error::new_spanned(&active.ty, "the 'active' field of a Component has to be a bool"));
+    }
+
+    Ok(quote! {
+        impl #impl_generics ::ecs::Component for #name #type_generics #where_clause {
+            fn set_active(&mut self, activate: bool) { self.active = activate; }
+            fn is_active(&self) -> &bool { &self.active }
+        }
+    })
+}
+
+/// Derives the ecs::Entity trait for a struct of components, and generates:
+///  - a 'has_<field>' methode for every component, telling if the component is active;
+///  - an enum named '<Struct>Component', with a variant for every component;
+///  - a 'component_mask' methode, returning a bitmask of all active components,
+///    where the bit of a component is given by '<Struct>Component::bit';
+///  - Entity::signature, returning the component mask as an ecs::Signature.
+///
+/// Fields that are not components can be excluded with '#[entity(skip)]'.
+/// An entity can have up to 64 components.
+///
+/// ```ignore
+/// #[derive(Default, Clone, Entity)]
+/// pub struct GameObject {
+///     pub position: Position,
+///     pub health: Health,
+///     #[entity(skip)]
+///     pub label: String,
+/// }
+///
+/// // generates GameObject::has_position, GameObjectComponent::Position, etc.
+/// ```
+///
+#[proc_macro_derive(Entity, attributes(entity))]
+pub fn derive_entity(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match entity(&input) {
+        Ok(output) => output.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn entity(input: &DeriveInput) -> Result<proc_macro2::TokenStream, Error> {
+    let name = &input.ident;
+    let vis = &input.vis;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let components = components(input, "Entity")?;
+
+    let enum_name = format_ident!("{}Component", name);
+    let count = components.len();
+    let variants: Vec<Ident> = components.iter().map(variant_name).collect();
+    let labels: Vec<String> = components.iter().map(field_name).collect();
+    let has_methods: Vec<Ident> = components.iter().map(|field| format_ident!("has_{}", field_name(field))).collect();
+    let bits: Vec<u32> = (0..count as u32).collect();
+
+    Ok(quote! {
+        impl #impl_generics ::ecs::Entity for #name #type_generics #where_clause {
+            fn signature(&self) -> Option<::ecs::Signature> {
+                Some(::ecs::Signature::from_bits(self.component_mask()))
+            }
+        }
+
+        impl #impl_generics #name #type_generics #where_clause {
+            #(
+                pub fn #has_methods(&self) -> bool { *::ecs::Component::is_active(&self.#components) }
+            )*
+
+            /// Returns if a component of this entity is active.
+            pub fn has(&self, component: #enum_name) -> bool {
+                match component {
+                    #( #enum_name::#variants => self.#has_methods(), )*
+                }
+            }
+
+            /// Returns a bitmask of all active components, see the bit methode of the component enum.
+            pub fn component_mask(&self) -> u64 {
+                let mut mask = 0;
+                #(
+                    if self.#has_methods() { mask |= #enum_name::#variants.bit(); }
+                )*
+                mask
+            }
+        }
+
+        /// All components of the entity, in the order they are declared.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #vis enum #enum_name {
+            #( #variants, )*
+        }
+
+        impl #enum_name {
+            pub const COUNT: usize = #count;
+            pub const ALL: [#enum_name; #count] = [ #( #enum_name::#variants, )* ];
+
+            /// The bit of this component within a component mask.
+            pub fn bit(self) -> u64 {
+                match self {
+                    #( #enum_name::#variants => 1u64 << #bits, )*
+                }
+            }
+
+            /// The name of the field holding this component.
+            pub fn name(self) -> &'static str {
+                match self {
+                    #( #enum_name::#variants => #labels, )*
+                }
+            }
+        }
+
+        impl From<#enum_name> for ::ecs::Signature {
+            fn from(component: #enum_name) -> ::ecs::Signature {
+                ::ecs::Signature::from_bits(component.bit())
+            }
+        }
+    })
+}
+
+/// Derives the ecs::Columnar trait for a struct of components, 
+/// so its objects can be stored per component type, see Scene::use_columns.
+/// Only active components are moved into the columns, each one marked with the same bit
+/// the Entity derive gives it, so systems can still be matched by their required and excluded signatures.
+///
+/// Fields excluded with '#[entity(skip)]' are dropped.
+///
+/// ```ignore
+/// #[derive(Default, Clone, Entity, Columnar)]
+/// pub struct GameObject {
+///     pub position: Position,
+///     pub health: Health,
+/// }
+/// ```
+///
+#[proc_macro_derive(Columnar, attributes(entity))]
+pub fn derive_columnar(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match columnar(&input) {
+        Ok(output) => output.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn columnar(input: &DeriveInput) -> Result<proc_macro2::TokenStream, Error> {
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let components = components(input, "Columnar")?;
+    let bits: Vec<u32> = (0..components.len() as u32).collect();
+
+    Ok(quote! {
+        impl #impl_generics ::ecs::Columnar for #name #type_generics #where_clause {
+            fn into_columns(self, pointer: ::ecs::Pointer, columns: &mut ::ecs::Columns) {
+                #(
+                    if *::ecs::Component::is_active(&self.#components) { 
+                        columns.insert_as(pointer, self.#components, ::ecs::Signature::from_bits(1u64 << #bits));
+                    }
+                )*
+            }
+        }
+    })
+}
+
+/// The fields of a struct that are components, in the order they are declared.
+fn components(input: &DeriveInput, derive: &str) -> Result<Vec<Ident>, Error> {
+    let mut components = Vec::new();
+    for field in named_fields(input, derive)? {
+        if !is_skipped(field)? {
+            components.push(field.ident.clone().unwrap());
+        }
+    }
+    if components.len() > 64 {
+        return Err(Error::new_spanned(&input.ident, format!("deriving {} supports up to 64 components", derive)));
+    }
+    Ok(components)
+}
+
+fn named_fields<'a>(input: &'a DeriveInput, derive: &str) -> Result<Vec<&'a Field>, Error> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => Ok(fields.named.iter().collect()),
+            _ => Err(Error::new_spanned(&input.ident, format!("deriving {} requires a struct with named fields", derive))),
+        },
+        _ => Err(Error::new_spanned(&input.ident, format!("deriving {} requires a struct", derive))),
+    }
+}
+
+fn is_skipped(field: &Field) -> Result<bool, Error> {
+    let mut skip = false;
+    for attribute in field.attrs.iter().filter(|attribute| attribute.path().is_ident("entity")) {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skip = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown entity attribute, expected 'skip'"))
+            }
+        })?;
+    }
+    Ok(skip)
+}
+
+fn is_bool(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path.qself.is_none() && path.path.is_ident("bool"),
+        _ => false,
+    }
+}
+
+/// The name of a field without the 'r#' of a raw identifier, so 'r#type' becomes 'type'.
+fn field_name(field: &Ident) -> String {
+    field.to_string().trim_start_matches("r#").to_string()
+}
+
+/// Turns a field name like 'move_to' into a variant name like 'MoveTo'.
+fn variant_name(field: &Ident) -> Ident {
+    let name = field_name(field);
+    let mut variant = String::new();
+    for part in name.split('_').filter(|part| !part.is_empty()) {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            variant.extend(first.to_uppercase());
+            variant.push_str(chars.as_str());
+        }
+    }
+    Ident::new(&variant, Span::call_site())
+}
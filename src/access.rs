@@ -0,0 +1,70 @@
+use std::any::TypeId;
+
+
+/// Access declares which components and resources a system reads and writes,
+/// so the Ecs can update systems that don't conflict at the same time, see SharedSystem::access.
+///
+/// ```ignore
+/// Access::new().read::<Movement>().write::<Position>().read_resource::<Settings>()
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Access {
+    reads: Vec<TypeId>,
+    writes: Vec<TypeId>,
+    resource_reads: Vec<TypeId>,
+    resource_writes: Vec<TypeId>,
+}
+
+impl Access {
+
+    pub fn new() -> Self { Self::default() }
+
+    pub fn read<C: 'static>(mut self) -> Self {
+        self.reads.push(TypeId::of::<C>());
+        self
+    }
+
+    pub fn write<C: 'static>(mut self) -> Self {
+        self.writes.push(TypeId::of::<C>());
+        self
+    }
+
+    pub fn read_resource<R: 'static>(mut self) -> Self {
+        self.resource_reads.push(TypeId::of::<R>());
+        self
+    }
+
+    pub fn write_resource<R: 'static>(mut self) -> Self {
+        self.resource_writes.push(TypeId::of::<R>());
+        self
+    }
+
+    /// Returns true if the two systems can't be updated at the same time,
+    /// because one of them writes data the other one reads or writes.
+    ///
+    /// When objects are stored whole in the pool, rather than per component in columns,
+    /// writing any component borrows the whole object, so it conflicts with any other component access.
+    ///
+    pub fn conflicts_with(&self, other: &Access, per_component: bool) -> bool {
+        let overlaps = |writes: &[TypeId], reads: &[TypeId], other_writes: &[TypeId]| {
+            writes.iter().any(|id| reads.contains(id) || other_writes.contains(id))
+        };
+
+        let components = match per_component {
+            true => overlaps(&self.writes, &other.reads, &other.writes) || overlaps(&other.writes, &self.reads, &self.writes),
+            false => (!self.writes.is_empty() && other.touches_components()) || (!other.writes.is_empty() && self.touches_components()),
+        };
+        components
+            || overlaps(&self.resource_writes, &other.resource_reads, &other.resource_writes)
+            || overlaps(&other.resource_writes, &self.resource_reads, &self.resource_writes)
+    }
+
+    pub(crate) fn writes_components(&self) -> bool {
+        !self.writes.is_empty()
+    }
+
+    fn touches_components(&self) -> bool {
+        !self.reads.is_empty() || !self.writes.is_empty()
+    }
+}
@@ -0,0 +1,183 @@
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::ops::{ Deref, DerefMut };
+use std::sync::atomic::{ AtomicUsize, Ordering };
+
+
+const WRITING: usize = usize::MAX;
+
+/// AtomicRefCell is a RefCell that can be shared between threads.
+/// Like RefCell it checks borrows at runtime, a conflicting borrow fails (or panics) instead of blocking.
+///
+/// The Scene stores its objects, columns and resources in AtomicRefCells,
+/// so systems running on different threads can borrow them at the same time.
+///
+pub struct AtomicRefCell<T: ?Sized> {
+    state: AtomicUsize, // number of shared borrows, or WRITING when borrowed mutably
+    value: UnsafeCell<T>,
+}
+
+// the borrow state makes sure a value is either shared or borrowed mutably by one thread at a time
+unsafe impl<T: ?Sized + Send> Send for AtomicRefCell<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for AtomicRefCell<T> {}
+
+/// BorrowError is returned when a value is already borrowed in a way that conflicts with the requested borrow.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BorrowError;
+
+impl fmt::Display for BorrowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "value is already borrowed")
+    }
+}
+
+impl std::error::Error for BorrowError {}
+
+impl<T> AtomicRefCell<T> {
+
+    pub fn new(value: T) -> Self {
+        AtomicRefCell { state: AtomicUsize::new(0), value: UnsafeCell::new(value) }
+    }
+
+    pub fn into_inner(self) -> T { self.value.into_inner() }
+
+    /// Replace the value, returning the old one. Panics if the value is currently borrowed.
+    ///
+    pub fn replace(&self, value: T) -> T {
+        std::mem::replace(&mut *self.borrow_mut(), value)
+    }
+}
+
+impl<T: ?Sized> AtomicRefCell<T> {
+
+    pub fn try_borrow(&self) -> Result<Ref<'_, T>, BorrowError> {
+        let mut state = self.state.load(Ordering::Relaxed);
+        loop {
+            if state >= WRITING - 1 { return Err(BorrowError); }
+
+            match self.state.compare_exchange_weak(state, state + 1, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => break,
+                Err(current) => state = current,
+            }
+        }
+        // the shared borrow is registered, so nobody can borrow the value mutably until it is released
+        let value = unsafe { &*self.value.get() };
+        Ok(Ref { value, borrow: BorrowRef { state: &self.state } })
+    }
+
+    pub fn try_borrow_mut(&self) -> Result<RefMut<'_, T>, BorrowError> {
+        if self.state.compare_exchange(0, WRITING, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            return Err(BorrowError);
+        }
+        // the mutable borrow is registered, so nobody else can borrow the value until it is released
+        let value = unsafe { &mut *self.value.get() };
+        Ok(RefMut { value, borrow: BorrowRefMut { state: &self.state } })
+    }
+
+    /// Borrow the value, panics if it is currently borrowed mutably.
+    ///
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.try_borrow().expect("value is already borrowed mutably")
+    }
+
+    /// Borrow the value mutably, panics if it is currently borrowed.
+    ///
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        self.try_borrow_mut().expect("value is already borrowed")
+    }
+
+    pub fn get_mut(&mut self) -> &mut T { self.value.get_mut() }
+}
+
+impl<T: Default> Default for AtomicRefCell<T> {
+    fn default() -> Self { AtomicRefCell::new(T::default()) }
+}
+
+struct BorrowRef<'a> {
+    state: &'a AtomicUsize,
+}
+
+impl Drop for BorrowRef<'_> {
+    fn drop(&mut self) { self.state.fetch_sub(1, Ordering::Release); }
+}
+
+struct BorrowRefMut<'a> {
+    state: &'a AtomicUsize,
+}
+
+impl Drop for BorrowRefMut<'_> {
+    fn drop(&mut self) { self.state.store(0, Ordering::Release); }
+}
+
+/// A shared borrow of the value in an AtomicRefCell.
+///
+pub struct Ref<'a, T: ?Sized> {
+    value: &'a T,
+    borrow: BorrowRef<'a>,
+}
+
+impl<'a, T: ?Sized> Ref<'a, T> {
+
+    /// Borrow a part of the value, like a field.
+    ///
+    pub fn map<U: ?Sized, F>(orig: Ref<'a, T>, f: F) -> Ref<'a, U>
+        where F: FnOnce(&T) -> &U
+    {
+        Ref { value: f(orig.value), borrow: orig.borrow }
+    }
+
+    /// Borrow a part of the value that might not be there, returns the original borrow if it is not.
+    ///
+    pub fn filter_map<U: ?Sized, F>(orig: Ref<'a, T>, f: F) -> Result<Ref<'a, U>, Ref<'a, T>>
+        where F: FnOnce(&T) -> Option<&U>
+    {
+        match f(orig.value) {
+            Some(value) => Ok(Ref { value, borrow: orig.borrow }),
+            None => Err(orig),
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for Ref<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T { self.value }
+}
+
+/// A mutable borrow of the value in an AtomicRefCell.
+///
+pub struct RefMut<'a, T: ?Sized> {
+    value: &'a mut T,
+    borrow: BorrowRefMut<'a>,
+}
+
+impl<'a, T: ?Sized> RefMut<'a, T> {
+
+    /// Borrow a part of the value mutably, like a field.
+    ///
+    pub fn map<U: ?Sized, F>(orig: RefMut<'a, T>, f: F) -> RefMut<'a, U>
+        where F: FnOnce(&mut T) -> &mut U
+    {
+        let RefMut { value, borrow } = orig;
+        RefMut { value: f(value), borrow }
+    }
+
+    /// Borrow a part of the value mutably that might not be there,
+    /// the borrow is released if it is not.
+    ///
+    pub fn filter_map<U: ?Sized, F>(orig: RefMut<'a, T>, f: F) -> Option<RefMut<'a, U>>
+        where F: FnOnce(&mut T) -> Option<&mut U>
+    {
+        let RefMut { value, borrow } = orig;
+        f(value).map(|value| RefMut { value, borrow })
+    }
+}
+
+impl<T: ?Sized> Deref for RefMut<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T { self.value }
+}
+
+impl<T: ?Sized> DerefMut for RefMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T { self.value }
+}
@@ -0,0 +1,72 @@
+use std::ops::{ Deref, DerefMut };
+use std::sync::{ Mutex, PoisonError };
+use std::sync::atomic::{ AtomicU64, Ordering };
+
+use crate::cell::RefMut;
+use crate::scene::Pointer;
+use crate::sparse::SparseSet;
+
+
+/// Tick is a moment in the life of a Scene, the scene advances its tick every time the Ecs runs a system.
+/// Objects are stamped with the tick at which they were last changed, see Scene::changed_since.
+///
+/// Tick 0 means never, the first tick of a scene is 1.
+///
+pub type Tick = u64;
+
+/// Mut is a mutable reference to an object in the scene, as returned by Scene::get_mut.
+/// When the object is accessed mutably, it is stamped with the current tick once Mut is dropped,
+/// and added to the touched objects of the scene, so the spatial index can move it.
+/// Scenes without a spatial index don't keep track of the touched objects, there is nothing to move.
+///
+/// Only reading the object through Mut does not count as a change.
+///
+pub struct Mut<'a, T> {
+    object: RefMut<'a, T>,
+    pointer: Pointer,
+    changed: &'a AtomicU64,
+    touched: Option<&'a Mutex<SparseSet<bool>>>,
+    tick: Tick,
+    mutated: bool,
+}
+
+impl<'a, T> Mut<'a, T> {
+
+    pub(crate) fn new(object: RefMut<'a, T>, pointer: Pointer, changed: &'a AtomicU64, touched: Option<&'a Mutex<SparseSet<bool>>>, tick: Tick) -> Self {
+        Mut { object, pointer, changed, touched, tick, mutated: false }
+    }
+
+    /// Returns true if the object has been accessed mutably through this reference.
+    ///
+    pub fn is_mutated(&self) -> bool { self.mutated }
+
+    /// Access the object mutably, without stamping it as changed.
+    ///
+    pub fn bypass_change_detection(&mut self) -> &mut T { &mut self.object }
+}
+
+impl<T> Deref for Mut<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T { &self.object }
+}
+
+impl<T> DerefMut for Mut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.mutated = true;
+        &mut self.object
+    }
+}
+
+impl<T> Drop for Mut<'_, T> {
+    fn drop(&mut self) {
+        if !self.mutated { return; }
+        self.changed.store(self.tick, Ordering::Relaxed);
+
+        // a change alone doesn't make the Ecs match the object to its systems again, see Scene::touch
+        let Some(touched) = self.touched else { return; };
+        let mut touched = touched.lock().unwrap_or_else(PoisonError::into_inner);
+        if !touched.contains(self.pointer) { 
+            touched.insert(self.pointer, false); 
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::any::{ Any, TypeId };
+use std::collections::HashMap;
+
+use crate::cell::{ AtomicRefCell, Ref, RefMut };
+use crate::scene::Pointer;
+use crate::signature::Signature;
+use crate::sparse::SparseSet;
+
+
+/// Column holds all components of one type, densely packed,
+/// together with the Pointers of the objects they belong to.
+///
+pub struct Column<C> {
+    components: SparseSet<C>,
+}
+
+impl<C> Default for Column<C> {
+    fn default() -> Self { Column { components: SparseSet::default() } }
+}
+
+impl<C> Column<C> {
+
+    pub fn insert(&mut self, pointer: Pointer, component: C) -> Option<C> {
+        self.components.insert(pointer, component)
+    }
+
+    pub fn remove(&mut self, pointer: Pointer) -> Option<C> {
+        self.components.remove(pointer)
+    }
+
+    pub fn contains(&self, pointer: Pointer) -> bool {
+        self.components.contains(pointer)
+    }
+
+    pub fn get(&self, pointer: Pointer) -> Option<&C> {
+        self.components.get(pointer)
+    }
+
+    pub fn get_mut(&mut self, pointer: Pointer) -> Option<&mut C> {
+        self.components.get_mut(pointer)
+    }
+
+    /// All components in the column, densely packed.
+    ///
+    pub fn components(&self) -> &[C] { self.components.values() }
+
+    pub fn components_mut(&mut self) -> &mut [C] { self.components.values_mut() }
+
+    /// The Pointers of the objects owning the components, in the same order as components().
+    ///
+    pub fn pointers(&self) -> &[Pointer] { self.components.pointers() }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Pointer, &C)> + '_ {
+        self.components.pointers().iter().copied().zip(self.components.values())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Pointer, &mut C)> + '_ {
+        let (pointers, components) = self.components.split_mut();
+        pointers.iter().copied().zip(components.iter_mut())
+    }
+
+    pub fn len(&self) -> usize { self.components.len() }
+
+    pub fn is_empty(&self) -> bool { self.components.is_empty() }
+}
+
+trait AnyColumn: Send + Sync {
+    fn remove_pointer(&mut self, pointer: Pointer);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<C: Send + Sync + 'static> AnyColumn for Column<C> {
+    fn remove_pointer(&mut self, pointer: Pointer) { self.remove(pointer); }
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+/// Columns is the storage of a Scene that stores its objects per component type,
+/// with one Column for each type of component, see Scene::use_columns.
+///
+/// Each column can be borrowed separately, so a system can read one column
+/// while writing to another.
+///
+/// Components inserted with insert_as mark their bit in the signature of the object,
+/// which the Ecs uses to match objects to systems, see Columns::signature.
+///
+#[derive(Default)]
+pub struct Columns {
+    columns: HashMap<TypeId, AtomicRefCell<Box<dyn AnyColumn>>>,
+    bits: HashMap<TypeId, Signature>, // the bit of every component type inserted with insert_as
+    signatures: SparseSet<Signature>, // the bits of the components of every object
+}
+
+impl Columns {
+
+    /// Add a component to the object at the Pointer position, creating its column if there is none.
+    /// Returns the component it replaces, if the object already had one of this type.
+    ///
+    pub fn insert<C: Send + Sync + 'static>(&mut self, pointer: Pointer, component: C) -> Option<C> {
+        let replaced = self.columns.entry(TypeId::of::<C>())
+            .or_insert_with(|| AtomicRefCell::new(Box::new(Column::<C>::default())))
+            .get_mut()
+            .as_any_mut()
+            .downcast_mut::<Column<C>>()
+            .unwrap()
+            .insert(pointer, component);
+
+        if let Some(bit) = self.bits.get(&TypeId::of::<C>()) {
+            let signature = self.signature(pointer).unwrap_or_default() | *bit;
+            self.signatures.insert(pointer, signature);
+        }
+        replaced
+    }
+
+    /// Same as insert, but also tells which bit of a Signature stands for the component type,
+    /// so later inserts and removes of this type update the signature of the object as well.
+    /// The Columnar derive uses the same bits as the Entity derive, see Entity::signature.
+    ///
+    pub fn insert_as<C: Send + Sync + 'static>(&mut self, pointer: Pointer, component: C, bit: Signature) -> Option<C> {
+        self.bits.insert(TypeId::of::<C>(), bit);
+        self.insert(pointer, component)
+    }
+
+    /// Remove the component of a type from the object at the Pointer position, if it has one.
+    ///
+    pub fn remove<C: Send + Sync + 'static>(&mut self, pointer: Pointer) -> Option<C> {
+        let removed = self.columns.get_mut(&TypeId::of::<C>())?
+            .get_mut()
+            .as_any_mut()
+            .downcast_mut::<Column<C>>()
+            .unwrap()
+            .remove(pointer)?;
+
+        if let (Some(bit), Some(signature)) = (self.bits.get(&TypeId::of::<C>()), self.signatures.get_mut(pointer)) {
+            *signature = signature.without(*bit);
+        }
+        Some(removed)
+    }
+
+    /// Remove all components of the object at the Pointer position.
+    ///
+    pub fn remove_all(&mut self, pointer: Pointer) {
+        for column in self.columns.values_mut() {
+            column.get_mut().remove_pointer(pointer);
+        }
+        self.signatures.remove(pointer);
+    }
+
+    /// The bits of the components of the object at the Pointer position, 
+    /// or None if none of its components were inserted with insert_as.
+    ///
+    pub fn signature(&self, pointer: Pointer) -> Option<Signature> {
+        self.signatures.get(pointer).copied()
+    }
+
+    /// Borrow the column of a component type, or None if no object ever had a component of this type.
+    /// Panics if the column is currently borrowed mutably.
+    ///
+    pub fn column<C: Send + Sync + 'static>(&self) -> Option<Ref<'_, Column<C>>> {
+        let column = self.columns.get(&TypeId::of::<C>())?;
+        Some(Ref::map(column.borrow(), |column| {
+            column.as_any().downcast_ref::<Column<C>>().unwrap()
+        }))
+    }
+
+    /// Same as column, but borrows the column mutably.
+    /// Panics if the column is currently borrowed.
+    ///
+    pub fn column_mut<C: Send + Sync + 'static>(&self) -> Option<RefMut<'_, Column<C>>> {
+        let column = self.columns.get(&TypeId::of::<C>())?;
+        Some(RefMut::map(column.borrow_mut(), |column| {
+            column.as_any_mut().downcast_mut::<Column<C>>().unwrap()
+        }))
+    }
+}
@@ -0,0 +1,67 @@
+use crate::spawns::Spawn;
+use crate::scene::Pointer;
+use crate::sparse::SparseSet;
+use crate::events::PendingEvents;
+
+
+/// Command is a single scene operation that has been deferred until the next sync point.
+///
+pub enum Command<E> {
+    Spawn(Spawn), // Spawn an object into the slot that was reserved for this spawn.
+    Destroy(Spawn),
+    Modify(Spawn, Box<dyn FnOnce(&mut E) + Send>),
+}
+
+/// Commands is a buffer of scene operations, that systems can fill while the scene is being updated.
+/// The Ecs applies all buffered commands, in the order they were added, at its sync points.
+///
+/// Use the defer_spawn, defer_destroy and defer_modify methodes of the Scene to add commands.
+///
+/// The free slots of the scene and the events sent by systems are kept here as well, 
+/// so systems sharing the scene can spawn and send events behind the same lock, see Scene::send.
+///
+pub struct Commands<E> {
+    queue: Vec<Command<E>>,
+    pub(crate) free: Vec<Pointer>, // free slots, used as a stack
+    pub(crate) reserved: SparseSet<Spawn>, // slots handed out by defer_spawn, of which the object is not spawned yet
+    pub(crate) slots: usize, // number of slots, including the slots reserved beyond the end of the pool
+    pub(crate) events: PendingEvents,
+}
+
+impl<E> Default for Commands<E> {
+    fn default() -> Self { 
+        Commands { 
+            queue: Vec::new(), 
+            free: Vec::new(), 
+            reserved: SparseSet::default(), 
+            slots: 0, 
+            events: PendingEvents::default(),
+        } 
+    }
+}
+
+impl<E> Commands<E> {
+
+    pub fn push(&mut self, command: Command<E>) {
+        self.queue.push(command);
+    }
+
+    pub fn len(&self) -> usize { self.queue.len() }
+
+    pub fn is_empty(&self) -> bool { self.queue.is_empty() }
+
+    /// Takes all buffered commands out of the buffer, leaving it empty.
+    ///
+    pub fn take(&mut self) -> Vec<Command<E>> {
+        std::mem::take(&mut self.queue)
+    }
+
+    /// Hands a drained queue back to the buffer, so its allocation can be reused.
+    ///
+    pub fn recycle(&mut self, mut queue: Vec<Command<E>>) {
+        if self.queue.is_empty() {
+            queue.clear();
+            self.queue = queue;
+        }
+    }
+}
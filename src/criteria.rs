@@ -0,0 +1,80 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::resources::Resources;
+
+
+type Condition = Arc<dyn Fn(&Resources) -> bool + Send + Sync>;
+
+/// RunCriteria tells the Ecs in which frames to update a system, see SystemOrder::run_if.
+/// A system is only updated when all of its criteria hold, by default it is updated every frame.
+///
+/// ```ignore
+/// SystemOrder::new(Stage::Update).run_if(RunCriteria::new().every(10).in_state(GameState::Combat))
+/// ```
+///
+#[derive(Clone)]
+pub struct RunCriteria {
+    every: u64,
+    conditions: Vec<Condition>,
+}
+
+impl Default for RunCriteria {
+    fn default() -> Self {
+        RunCriteria { every: 1, conditions: Vec::new() }
+    }
+}
+
+impl fmt::Debug for RunCriteria {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RunCriteria")
+            .field("every", &self.every)
+            .field("conditions", &self.conditions.len())
+            .finish()
+    }
+}
+
+impl RunCriteria {
+
+    pub fn new() -> Self { Self::default() }
+
+    /// Only update the system once every so many frames, starting with the first frame.
+    ///
+    pub fn every(mut self, frames: u64) -> Self {
+        assert!(frames > 0, "a system can not run every zero frames");
+        self.every = frames;
+        self
+    }
+
+    /// Only update the system while the predicate holds for the resource of type R.
+    /// The system is not updated while there is no such resource.
+    ///
+    pub fn when<R, F>(mut self, predicate: F) -> Self
+        where R: Send + Sync + 'static, F: Fn(&R) -> bool + Send + Sync + 'static
+    {
+        self.conditions.push(Arc::new(move |resources: &Resources| {
+            resources.get::<R>().is_ok_and(|resource| predicate(&resource))
+        }));
+        self
+    }
+
+    /// Only update the system while the state, kept as a resource, equals the given state.
+    ///
+    /// ```ignore
+    /// RunCriteria::new().in_state(GameState::Paused)
+    /// ```
+    ///
+    pub fn in_state<S: PartialEq + Send + Sync + 'static>(self, state: S) -> Self {
+        self.when(move |current: &S| *current == state)
+    }
+
+    /// Tells if the system should be updated in the frame, see Time::frame.
+    ///
+    // u64::is_multiple_of needs rust 1.87, the modulo keeps older toolchains building
+    #[allow(clippy::manual_is_multiple_of)]
+    pub fn should_run(&self, frame: u64, resources: &Resources) -> bool {
+        // most systems run every frame, skip the division for them
+        (self.every == 1 || frame.saturating_sub(1) % self.every == 0)
+            && self.conditions.iter().all(|condition| condition(resources))
+    }
+}
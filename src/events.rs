@@ -0,0 +1,215 @@
+use std::any::{ Any, TypeId };
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+
+/// Events is a double buffered queue of events of one type.
+/// Events sent during a frame can be read during that frame and the next one,
+/// after which they are dropped, see Events::swap.
+///
+/// Every event gets an id, counting up from 0, which EventReaders use to remember
+/// which events they have already read.
+///
+pub struct Events<Ev> {
+    previous: Vec<Ev>,
+    current: Vec<Ev>,
+    previous_start: usize, // id of the first event in previous
+}
+
+impl<Ev> Default for Events<Ev> {
+    fn default() -> Self {
+        Events { previous: Vec::new(), current: Vec::new(), previous_start: 0 }
+    }
+}
+
+impl<Ev> Events<Ev> {
+
+    pub fn send(&mut self, event: Ev) {
+        self.current.push(event);
+    }
+
+    /// Returns all events that the reader has not read yet, moving the reader past every event that is itterated over.
+    /// Events that are not itterated over, like after a break, are read the next time.
+    /// Events that have already been dropped before the reader got to them are skipped.
+    ///
+    pub fn read<'a>(&'a self, reader: &'a mut EventReader<Ev>) -> ReadEvents<'a, Ev> {
+        ReadEvents { events: Some(self), reader }
+    }
+
+    /// Returns the event with the id, if it is still buffered.
+    ///
+    fn get(&self, id: usize) -> Option<&Ev> {
+        let index = id.checked_sub(self.previous_start)?;
+        match index.checked_sub(self.previous.len()) {
+            Some(index) => self.current.get(index),
+            None => self.previous.get(index),
+        }
+    }
+
+    /// Returns the number of events the reader has not read yet.
+    ///
+    pub fn unread(&self, reader: &EventReader<Ev>) -> usize {
+        self.end() - reader.next.max(self.previous_start).min(self.end())
+    }
+
+    /// Returns the number of buffered events, of this frame and the previous one.
+    ///
+    pub fn len(&self) -> usize { self.previous.len() + self.current.len() }
+
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Drops the events of the previous frame, the events of this frame become the previous events.
+    /// The Ecs swaps all event queues at the end of every frame.
+    ///
+    pub fn swap(&mut self) {
+        self.previous_start += self.previous.len();
+        std::mem::swap(&mut self.previous, &mut self.current);
+        self.current.clear();
+    }
+
+    fn end(&self) -> usize { self.previous_start + self.len() }
+}
+
+/// EventReader is a cursor into the events of one type, so every system can read every event once.
+/// A new reader starts with the oldest events that are still buffered.
+///
+pub struct EventReader<Ev> {
+    next: usize, // id of the next event to read
+    event: PhantomData<fn() -> Ev>,
+}
+
+impl<Ev> Default for EventReader<Ev> {
+    fn default() -> Self { EventReader { next: 0, event: PhantomData } }
+}
+
+impl<Ev> Clone for EventReader<Ev> {
+    fn clone(&self) -> Self { EventReader { next: self.next, event: PhantomData } }
+}
+
+impl<Ev> EventReader<Ev> {
+    pub fn new() -> Self { Self::default() }
+}
+
+/// ReadEvents itterates over the events an EventReader has not read yet, see Events::read.
+///
+pub struct ReadEvents<'a, Ev> {
+    events: Option<&'a Events<Ev>>,
+    reader: &'a mut EventReader<Ev>,
+}
+
+impl<'a, Ev> ReadEvents<'a, Ev> {
+    pub(crate) fn new(events: Option<&'a Events<Ev>>, reader: &'a mut EventReader<Ev>) -> Self {
+        ReadEvents { events, reader }
+    }
+}
+
+impl<'a, Ev> Iterator for ReadEvents<'a, Ev> {
+    type Item = &'a Ev;
+
+    fn next(&mut self) -> Option<&'a Ev> {
+        let events = self.events?;
+        let id = self.reader.next.max(events.previous_start);
+        let event = events.get(id)?;
+        self.reader.next = id + 1;
+        Some(event)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let unread = self.events.map_or(0, |events| events.unread(self.reader));
+        (unread, Some(unread))
+    }
+}
+
+trait AnyEvents: Send + Sync {
+    fn swap(&mut self);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<Ev: Send + Sync + 'static> AnyEvents for Events<Ev> {
+    fn swap(&mut self) { Events::swap(self) }
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+/// EventChannels holds the event queues of all event types, see Scene::send.
+///
+#[derive(Default)]
+pub struct EventChannels {
+    channels: HashMap<TypeId, Box<dyn AnyEvents>>,
+}
+
+impl EventChannels {
+
+    pub fn get<Ev: Send + Sync + 'static>(&self) -> Option<&Events<Ev>> {
+        self.channels.get(&TypeId::of::<Ev>())
+            .map(|events| events.as_any().downcast_ref::<Events<Ev>>().unwrap())
+    }
+
+    /// Returns the queue of an event type, creating it if there is none.
+    ///
+    pub fn get_or_insert<Ev: Send + Sync + 'static>(&mut self) -> &mut Events<Ev> {
+        self.channels.entry(TypeId::of::<Ev>())
+            .or_insert_with(|| Box::new(Events::<Ev>::default()))
+            .as_any_mut()
+            .downcast_mut::<Events<Ev>>()
+            .unwrap()
+    }
+
+    pub fn swap_all(&mut self) {
+        for events in self.channels.values_mut() {
+            events.swap();
+        }
+    }
+}
+
+trait AnyPending: Send {
+    fn flush(&mut self, channels: &mut EventChannels);
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<Ev: Send + Sync + 'static> AnyPending for Vec<Ev> {
+    fn flush(&mut self, channels: &mut EventChannels) {
+        let events = channels.get_or_insert::<Ev>();
+        for event in self.drain(..) {
+            events.send(event);
+        }
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+/// PendingEvents holds the events sent through a shared scene, per event type,
+/// until they are moved into the EventChannels, see Scene::apply_events.
+///
+#[derive(Default)]
+pub struct PendingEvents {
+    pending: HashMap<TypeId, Box<dyn AnyPending>>,
+    count: usize,
+}
+
+impl PendingEvents {
+
+    pub fn push<Ev: Send + Sync + 'static>(&mut self, event: Ev) {
+        self.pending.entry(TypeId::of::<Ev>())
+            .or_insert_with(|| Box::new(Vec::<Ev>::new()))
+            .as_any_mut()
+            .downcast_mut::<Vec<Ev>>()
+            .unwrap()
+            .push(event);
+        self.count += 1;
+    }
+
+    pub fn len(&self) -> usize { self.count }
+
+    pub fn is_empty(&self) -> bool { self.count == 0 }
+
+    /// Sends all pending events, keeping the buffers so they can be reused.
+    ///
+    pub fn flush_into(&mut self, channels: &mut EventChannels) {
+        if self.count == 0 { return; }
+        for pending in self.pending.values_mut() {
+            pending.flush(channels);
+        }
+        self.count = 0;
+    }
+}
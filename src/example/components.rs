@@ -3,11 +3,12 @@
 
 use std::collections::VecDeque;
 
-use crate::types::{ Entity, Component };
+use crate::{ Entity, Component, Columnar };
 use crate::spawns::Spawn;
+use crate::spatial::Point;
 
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Entity, Columnar)]
 pub struct GameObject {
     pub position: Position,
     pub agenda: Agenda,
@@ -23,26 +24,12 @@ pub struct GameObject {
 }
 
 impl GameObject {
-    pub fn has_position(&self) -> bool { *Component::is_active(&self.position) }
-    pub fn has_agenda(&self) -> bool { *Component::is_active(&self.agenda) }
-    pub fn has_movement(&self) -> bool { *Component::is_active(&self.movement) }
-    pub fn has_health(&self) -> bool { *Component::is_active(&self.health) }
-    pub fn has_focus(&self) -> bool { *Component::is_active(&self.focus) }
-    pub fn has_attack(&self) -> bool { *Component::is_active(&self.attack) }
-    pub fn has_damage(&self) -> bool { *Component::is_active(&self.damage) }
-    pub fn has_defense(&self) -> bool { *Component::is_active(&self.defense) }
-    pub fn has_resist(&self) -> bool { *Component::is_active(&self.resist) }
-    pub fn has_afflictions(&self) -> bool { *Component::is_active(&self.afflictions) }
-    pub fn has_carry(&self) -> bool { *Component::is_active(&self.carry) }
-}
-
-impl Entity for GameObject {
-
+    pub fn location(&self) -> Point { (self.position.x, self.position.y) }
 }
 
 // --component types--
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Component)]
 pub struct Movement {
     active: bool,
     speed: f32,
@@ -56,13 +43,9 @@ impl Movement {
         &self.speed
     }
 }
-impl Component for Movement {
-    fn set_active(&mut self, activate: bool) { self.active = activate; }
-    fn is_active(&self) -> &bool { &self.active }
-}
 
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Component)]
 pub struct Damage {
     active: bool,
     incoming: VecDeque<Attack>,
@@ -75,10 +58,6 @@ impl Damage {
         self.incoming.push_back(attack)
     }
 }
-impl Component for Damage {
-    fn set_active(&mut self, activate: bool) { self.active = activate; }
-    fn is_active(&self) -> &bool { &self.active }
-}
 impl Iterator for Damage {
     type Item = Attack;
     fn next(&mut self) -> Option<Self::Item> {
@@ -87,7 +66,7 @@ impl Iterator for Damage {
 }
 
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Component)]
 pub struct Defense {
     active: bool,
     blockers: Vec<ImpactProtection>,
@@ -97,7 +76,7 @@ impl Defense {
         Defense { active: true, blockers, }
     }
     pub fn resolve_attack(&self, attack: &Attack) -> u32 {
-        let mut power = attack.weapon.power.clone();
+        let mut power = attack.weapon.power;
         for blocker in &self.blockers {
             if blocker.against == attack.weapon.impact {
                 power = (power as f32 * blocker.immunity_factor.as_f32()) as u32;
@@ -107,13 +86,9 @@ impl Defense {
         power
     } 
 }
-impl Component for Defense {
-    fn set_active(&mut self, activate: bool) { self.active = activate; }
-    fn is_active(&self) -> &bool { &self.active }
-}
 
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Component)]
 pub struct Resist {
     active: bool,
     resistances: Vec<AfflictionProtection>,
@@ -123,20 +98,14 @@ impl Resist {
         Resist { active: true, resistances, }
     }
 }
-impl Component for Resist {
-    fn set_active(&mut self, activate: bool) { self.active = activate; }
-    fn is_active(&self) -> &bool { &self.active }
-}
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub enum ImmunityFactor {
+    #[default]
     None,
     Half,
     Full,
 }
-impl Default for ImmunityFactor {
-    fn default() -> Self { ImmunityFactor::None }
-}
 impl ImmunityFactor {
     fn as_f32(&self) -> f32 { 
         match self {
@@ -162,7 +131,7 @@ pub struct AfflictionProtection {
 }
 
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Component)]
 pub struct Health {
     active: bool,
     pub current_hp: u32,
@@ -180,13 +149,9 @@ impl Health {
         self.current_hp -= hp;
     }
 }
-impl Component for Health {
-    fn set_active(&mut self, activate: bool) { self.active = activate; }
-    fn is_active(&self) -> &bool { &self.active }
-}
 
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Component)]
 pub struct Focus {
     active: bool,
     focus: Vec<Spawn>,
@@ -201,7 +166,7 @@ impl Focus {
         }
     }
     pub fn prime(&self) -> Option<&Spawn> {
-        if self.focus.len() > 0 {
+        if !self.focus.is_empty() {
             Some(&self.focus[0])
         } else {
             None
@@ -219,13 +184,9 @@ impl Focus {
         self.focus.len()
     }
 }
-impl Component for Focus {
-    fn set_active(&mut self, activate: bool) { self.active = activate; }
-    fn is_active(&self) -> &bool { &self.active }
-}
 
 
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Component)]
 pub struct Attack {
     pub active: bool,
     pub weapon: Weapon,
@@ -237,10 +198,6 @@ impl Attack {
         self.weapon.power + self.skill
     }
 }
-impl Component for Attack {
-    fn set_active(&mut self, activate: bool) { self.active = activate; }
-    fn is_active(&self) -> &bool { &self.active }
-}
 
 #[derive(Clone, Default)]
 pub struct Weapon {
@@ -346,19 +303,18 @@ impl Weapon {
         range: 0.0,
     }}
 }
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Default)]
 pub enum Impact {
+    #[default]
     Bashing,
     Piercing,
     Cutting,
     Exploding,
     Mental,
 }
-impl Default for Impact {
-    fn default() -> Self { Impact::Bashing }
-}
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Default)]
 pub enum Affliction {
+    #[default]
     Annoyed,
     Confused,
     Dazzled,
@@ -370,43 +326,29 @@ pub enum Affliction {
     Poisoned,
     Diseased,
 }
-impl Default for Affliction {
-    fn default() -> Self { Affliction::Annoyed }
-}
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Component)]
 pub struct Afflictions { 
     active: bool,
     list: Vec<Affliction>,
 }
-impl Component for Afflictions {
-    fn set_active(&mut self, activate: bool) { self.active = activate }
-    fn is_active(&self) -> &bool { &self.active }
-}
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Component)]
 pub struct Carry {
     active: bool,
     spawns: Vec<Spawn>,
 }
-impl Component for Carry {
-    fn set_active(&mut self, activate: bool) { self.active = activate; }
-    fn is_active(&self) -> &bool { &self.active }
-}
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Component)]
 pub struct Agenda {
     active: bool,
     pub faction: Faction,
 }
-impl Component for Agenda {
-    fn set_active(&mut self, activate: bool) { self.active = activate; }
-    fn is_active(&self) -> &bool { &(self.active) }
-}
 
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Default)]
 pub enum Faction {
+    #[default]
     None,
     Red,
     Bleu,
@@ -418,12 +360,9 @@ impl Faction {
         && other != self
     }
 }
-impl Default for Faction {
-    fn default() -> Self { Faction::None }
-}
 
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Component)]
 pub struct Position {
     active: bool,
     pub x: f64,
@@ -436,7 +375,3 @@ impl Position {
         ((diff_x * diff_x) + (diff_y * diff_y)).sqrt()
     }
 }
-impl Component for Position {
-    fn set_active(&mut self, activate: bool) { self.active = activate; }
-    fn is_active(&self) -> &bool { &self.active }
-}
@@ -0,0 +1,16 @@
+use crate::spawns::Spawn;
+
+
+/// Sent by the AttackSystem, every time an object attacks another.
+/// 
+#[derive(Clone)]
+pub struct WeaponFired {
+    pub by: Spawn,
+    pub at: Spawn,
+    pub power: u32,
+}
+
+/// Sent by the DamageSystem, when the health of an object drops to zero.
+/// 
+#[derive(Clone)]
+pub struct Died(pub Spawn);
@@ -18,6 +18,10 @@ impl Soldier {
     pub fn group(&self) -> &Group { &self.group } 
 }
 
+impl Default for Soldier {
+    fn default() -> Self { Self::new() }
+}
+
 impl Factory<GameObject> for Soldier {
 
     fn init(&mut self, group: Group) {
@@ -67,6 +71,10 @@ impl Truck {
     pub fn group(&self) -> &Group { &self.group } 
 }
 
+impl Default for Truck {
+    fn default() -> Self { Self::new() }
+}
+
 impl Factory<GameObject> for Truck {
 
     fn init(&mut self, group: Group) {
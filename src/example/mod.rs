@@ -2,3 +2,4 @@
 pub mod components;
 pub mod systems;
 pub mod factories;
+pub mod events;
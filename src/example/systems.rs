@@ -1,25 +1,70 @@
 
 #![allow(unused_variables)]
 
-use crate::scene::Scene;
+use crate::scene::{ Scene, SceneError };
 use crate::spawns::Spawn;
-use crate::types::System;
+use crate::types::{ System, SharedSystem };
+use crate::signature::Signature;
+use crate::access::Access;
 
 use super::components::*;
+use super::events::*;
 
 
 pub struct MoveSystem;
 
 impl System<GameObject> for MoveSystem {
 
-    fn requirements(&self, target: &GameObject) -> bool {
-        target.has_position()
-        && target.has_movement()
+    fn required(&self) -> Signature {
+        Signature::of([GameObjectComponent::Position, GameObjectComponent::Movement])
     }
 
     fn update(&mut self, spawn: &Spawn, scene: &mut Scene<GameObject>) {
         let target = &mut scene.get_mut(spawn);
-        target.position.x += *target.movement.speed() as f64;
+        target.position.x += *target.movement.speed() as f64 * scene.time().delta();
+    }
+}
+
+
+/// Same as the MoveSystem, but for scenes that store their objects in columns.
+/// It only touches the position and movement columns, instead of whole objects.
+/// 
+pub struct ColumnMoveSystem;
+
+impl System<GameObject> for ColumnMoveSystem {
+
+    // columnar objects are matched by the components they have in the columns
+    fn required(&self) -> Signature {
+        Signature::of([GameObjectComponent::Position, GameObjectComponent::Movement])
+    }
+
+    fn update(&mut self, spawn: &Spawn, scene: &mut Scene<GameObject>) {
+        let Some(speed) = scene.component::<Movement>(spawn).map(|movement| *movement.speed())
+            else { return };
+        let delta = scene.time().delta();
+        if let Some(mut position) = scene.component_mut::<Position>(spawn) {
+            position.x += speed as f64 * delta;
+        }
+    }
+}
+
+impl SharedSystem<GameObject> for ColumnMoveSystem {
+
+    // only borrowing the columns it needs, lets the system run next to systems using other columns
+    fn access(&self) -> Access {
+        Access::new().write::<Position>().read::<Movement>()
+    }
+
+    fn update_shared(&mut self, matches: &[Spawn], scene: &Scene<GameObject>) {
+        let (Some(mut positions), Some(movements)) = (scene.column_mut::<Position>(), scene.column::<Movement>())
+            else { return };
+
+        let delta = scene.time().delta();
+        for spawn in matches {
+            if let (Some(position), Some(movement)) = (positions.get_mut(*spawn.pointer()), movements.get(*spawn.pointer())) {
+                position.x += *movement.speed() as f64 * delta;
+            }
+        }
     }
 }
 
@@ -28,36 +73,58 @@ pub struct AttackSystem;
 
 impl System<GameObject> for AttackSystem {
 
-    fn requirements(&self, target: &GameObject) -> bool {
-        target.has_position()
-        && target.has_focus()
-        && target.has_attack()
-        && target.has_agenda()
+    fn required(&self) -> Signature {
+        Signature::of([
+            GameObjectComponent::Position, 
+            GameObjectComponent::Focus, 
+            GameObjectComponent::Attack, 
+            GameObjectComponent::Agenda,
+        ])
     }
 
     fn update(&mut self, spawn: &Spawn, scene: &mut Scene<GameObject>) {
-        let target = &mut scene.get_mut(spawn);
+        let mut target = scene.get_mut(spawn);
+        let mut fired = None;
 
         // if target has a focus, than attack the first focus
-        if let Some(other_spawn) = target.focus.prime() {
+        if let Some(other_spawn) = target.focus.prime().cloned() {
             
-            let opponent = &mut scene.get_mut(other_spawn);
-
-            if opponent.has_health() {
-                opponent.damage.take_damage(target.attack.clone());
+            match scene.try_get_mut(&other_spawn) {
+                Ok(mut opponent) => if opponent.has_health() {
+                    opponent.damage.take_damage(target.attack.clone());
+                    fired = Some(WeaponFired { by: spawn.clone(), at: other_spawn, power: target.attack.power() });
+                },
+                // the focus has been destroyed, or it is no longer the object the target focused on
+                Err(SceneError::Destroyed) | Err(SceneError::StaleHandle) => target.focus.remove(&other_spawn),
+                Err(_) => (),
             }
         
         // if target doesn't have a focus find and add a new one
         } else {
 
-            if let Some(spawn) = scene.search_components(|other| {
+            let is_opponent = |other: &GameObject| {
                 other.has_damage() 
                 && target.agenda.faction.opposing(&other.agenda.faction)
                 && target.position.distance(&other.position) < 10.0
-            }) {
+            };
+
+            // use the spatial index if the scene has one, otherwise check all objects
+            let opponent = match scene.within_radius(target.location(), 10.0) {
+                Ok(in_range) => in_range.into_iter()
+                    .find(|spawn| scene.try_get_ref(spawn).is_ok_and(|other| is_opponent(&other))),
+                Err(_) => scene.query(is_opponent).next().map(|(spawn, _)| spawn),
+            };
+
+            if let Some(spawn) = opponent {
                 target.focus.add(&spawn);
             }
         }
+
+        // the target has to be released before the scene can be changed
+        drop(target);
+        if let Some(event) = fired {
+            scene.send(event);
+        }
     }
 }
 
@@ -66,20 +133,28 @@ pub struct DamageSystem;
 
 impl System<GameObject> for DamageSystem {
 
-    fn requirements(&self, target: &GameObject) -> bool {
-        target.has_health()
-        && target.has_damage()
+    fn required(&self) -> Signature {
+        Signature::of([GameObjectComponent::Health, GameObjectComponent::Damage])
     }
 
     fn update(&mut self, spawn: &Spawn, scene: &mut Scene<GameObject>) {
-        let target = &mut scene.get_mut(spawn);
+        let mut target = scene.get_mut(spawn);
+        let alive = target.health.current_hp > 0;
 
         for attack in target.damage.clone() {
             let power = match target.has_defense() {
                 true => target.defense.resolve_attack(&attack),
                 false => attack.power(),
             };
+            // health stops at zero, so the system can tell when the target died
+            let power = power.min(target.health.current_hp);
             target.health.damage(power)
         }
+
+        let died = alive && target.health.current_hp == 0;
+        drop(target);
+        if died {
+            scene.send(Died(spawn.clone()));
+        }
     }
 }
@@ -1,35 +1,460 @@
 
+// lets the derive macros refer to this crate as ::ecs, also from within this crate
+extern crate self as ecs;
+
 pub mod scene;
 pub mod types;
 pub mod spawns;
+pub mod sparse;
+pub mod commands;
+pub mod spatial;
+pub mod columns;
+pub mod cell;
+pub mod access;
+pub mod signature;
+pub mod changes;
+pub mod events;
+pub mod resources;
+pub mod time;
+pub mod schedule;
+pub mod criteria;
+pub mod workers;
 
 pub mod example;
 
 pub use crate::scene::*;
 pub use crate::types::*;
+pub use crate::commands::*;
+pub use crate::spatial::*;
+pub use crate::columns::*;
+pub use crate::signature::*;
+pub use crate::access::*;
+pub use crate::changes::*;
+pub use crate::events::*;
+pub use crate::resources::*;
+pub use crate::time::*;
+pub use crate::schedule::{ Stage, SystemOrder, SystemId, ScheduleError };
+pub use crate::criteria::*;
+pub use ecs_derive::{ Component, Entity, Columnar };
+
+use std::any::TypeId;
+use std::ops::Range;
+use std::sync::Mutex;
+
+use crate::cell::{ Ref, RefMut };
+
+use crate::sparse::SparseSet;
+use crate::spawns::{ Spawn, Group };
+use crate::schedule::{ Entry, Parallel };
+
+
+/// SyncPoint tells the Ecs when to apply the commands that systems buffered in the scene,
+/// using the defer_spawn, defer_destroy and defer_modify methodes.
+/// 
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SyncPoint {
+    #[default]
+    EachSystem, // Apply after every system, later systems see the result.
+    EndOfFrame, // Apply once, after all systems have been updated.
+}
+
+/// A registered system, shared systems can be updated at the same time as other shared systems.
+/// Shared systems are stored apart from the others, so only they are handed to the worker threads.
+/// 
+enum Registered<E: Entity> {
+    Exclusive(Box::<dyn System<E>>),
+    Shared(usize), // position in the shared systems
+}
+
+impl<E: Entity> Registered<E> {
+    fn system<'a>(&'a self, shared: &'a [Shared<E>]) -> &'a dyn System<E> {
+        match self {
+            Registered::Exclusive(sys) => sys.as_ref(),
+            Registered::Shared(slot) => shared[*slot].system.as_ref(),
+        }
+    }
+
+    fn system_mut<'a>(&'a mut self, shared: &'a mut [Shared<E>]) -> &'a mut dyn System<E> {
+        match self {
+            Registered::Exclusive(sys) => sys.as_mut(),
+            Registered::Shared(slot) => shared[*slot].system.as_mut(),
+        }
+    }
+}
+
+struct Shared<E: Entity> {
+    index: usize, // position in the registered systems
+    system: Box::<dyn SharedSystem<E>>,
+}
 
 
 pub struct Ecs<E: Entity> {
     scene: Scene<E>,
-    systems: Vec<Box::<dyn System<E>>>,
+    systems: Vec<Registered<E>>,
+    shared: Vec<Shared<E>>,
+    matches: Vec<SparseSet<Spawn>>,
+    transitions: Vec<(usize, Spawn, bool)>, // system, object and whether it entered or exited the matches
+    last_run: Vec<Tick>,
+    changed: Vec<Vec<Spawn>>, // per system, the matches that changed since it last ran
+    running: Vec<usize>, // the systems of the current batch that meet their run criteria
+    types: Vec<TypeId>,
+    enabled: Vec<bool>,
+    criteria: Vec<RunCriteria>,
+    startup: Vec<SystemId>,
+    schedule: Vec<SystemId>,
+    startup_batches: Vec<Range<usize>>,
+    batches: Vec<Range<usize>>,
+    started: bool,
+    sync_point: SyncPoint,
+    timestep: Timestep,
+    accumulator: f64,
 }
 
 impl<E: Entity> Ecs<E> {
 
-    pub fn update(&mut self) {
-        for sys in &mut self.systems {
-            for spawn in self.scene.list_spawned() {
-                if sys.requirements(&self.scene.get_mut(&spawn)) {
-                    sys.update(&spawn, &mut self.scene);
+    /// Update the systems, 'delta' is the time passed since the previous update in seconds.
+    /// With a fixed timestep, the systems are updated as many steps as fit in the time passed,
+    /// the remaining time is carried over to the next update.
+    /// 
+    pub fn update(&mut self, delta: f64) {
+        match self.timestep {
+            Timestep::Variable => {
+                self.scene.time_mut().advance(delta);
+                self.scene.time_mut().set_alpha(1.0);
+                self.run_systems();
+            },
+            Timestep::Fixed { hz, max_steps } => {
+                let step = 1.0 / hz;
+                self.accumulator += delta;
+
+                let mut steps = 0;
+                while self.accumulator >= step && steps < max_steps {
+                    self.scene.time_mut().advance(step);
+                    self.run_systems();
+                    self.accumulator -= step;
+                    steps += 1;
+                }
+                // when the systems can't keep up, drop the time that was not simulated,
+                // otherwise every update would have to run more steps than the last
+                if self.accumulator >= step {
+                    self.accumulator %= step;
+                }
+                self.scene.time_mut().set_alpha(self.accumulator / step);
+            },
+        }
+    }
+
+    fn run_systems(&mut self) {
+        if !self.started {
+            self.started = true;
+            let (startup, batches) = (std::mem::take(&mut self.startup), std::mem::take(&mut self.startup_batches));
+            for batch in &batches {
+                self.run_batch(&startup[batch.clone()]);
+            }
+            (self.startup, self.startup_batches) = (startup, batches);
+        }
+        // the schedule is taken out while running it, so systems can be borrowed mutably without copying it
+        let (schedule, batches) = (std::mem::take(&mut self.schedule), std::mem::take(&mut self.batches));
+        for batch in &batches {
+            self.run_batch(&schedule[batch.clone()]);
+        }
+        (self.schedule, self.batches) = (schedule, batches);
+        if self.sync_point == SyncPoint::EndOfFrame {
+            self.sync();
+        }
+        self.scene.update_events();
+
+        // changes made in between updates are newer than the last run of every system
+        self.scene.advance_tick();
+    }
+
+    /// Update a batch of systems at the same time, spread over the threads of the scene.
+    /// Systems in the same batch share a tick and don't see eachothers deferred commands until the batch is done.
+    /// Batches of more than one system only hold shared systems, see schedule::batches.
+    ///
+    fn run_batch(&mut self, batch: &[SystemId]) {
+        if batch.len() == 1 {
+            if self.should_run(batch[0].0) { self.run_system(batch[0].0); }
+            return;
+        }
+        if !batch.iter().any(|id| self.should_run(id.0)) { return; }
+        self.scene.apply_events();
+        self.refresh_matches();
+        self.scene.set_iterating(true);
+        let tick = self.scene.advance_tick();
+
+        let mut running = std::mem::take(&mut self.running);
+        running.clear();
+        running.extend(batch.iter().map(|id| id.0).filter(|index| self.should_run(*index)));
+        for index in &running {
+            self.collect_changed(*index);
+        }
+        let threads = self.scene.threads().min(running.len());
+        let (scene, matches, changed) = (&self.scene, &self.matches, &self.changed);
+        // every call below takes the next system that has to run, the iterator doesn't allocate
+        let jobs = Mutex::new(self.shared.iter_mut().filter(|shared| running.contains(&shared.index)));
+
+        scene.for_each_parallel(running.len(), threads, &|_| {
+            let job = jobs.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).next();
+            let Some(Shared { index, system }) = job else { return; };
+            match system.only_changed() {
+                true => system.update_shared(&changed[*index], scene),
+                false => system.update_shared(matches[*index].values(), scene),
+            }
+        });
+
+        for index in &running {
+            self.last_run[*index] = tick;
+        }
+        self.running = running;
+        self.scene.set_iterating(false);
+
+        if self.sync_point == SyncPoint::EachSystem {
+            self.sync();
+        }
+    }
+
+    fn run_system(&mut self, index: usize) {
+        // events sent by the systems before are readable, even when commands are only applied at the end of the frame
+        self.scene.apply_events();
+        self.refresh_matches();
+
+        // Objects spawned by the system will only be updated by the next system,
+        // destroyed objects stay in place until the sync point.
+        self.scene.set_iterating(true);
+        let tick = self.scene.advance_tick();
+
+        self.collect_changed(index);
+        self.update_system(index);
+        self.last_run[index] = tick;
+        self.scene.set_iterating(false);
+
+        if self.sync_point == SyncPoint::EachSystem {
+            self.sync();
+        }
+    }
+
+    /// Tells if a system is enabled and meets its run criteria this frame.
+    /// 
+    fn should_run(&self, index: usize) -> bool {
+        self.enabled[index] && self.criteria[index].should_run(self.scene.time().frame(), self.scene.resources())
+    }
+
+    /// Collects the matches of a system that changed since it last ran, if the system only updates those.
+    /// 
+    fn collect_changed(&mut self, index: usize) {
+        if !self.systems[index].system(&self.shared).only_changed() { return; }
+        let (last_run, scene) = (self.last_run[index], &self.scene);
+        self.changed[index].clear();
+        self.changed[index].extend(self.matches[index].values().iter()
+            .filter(|spawn| scene.is_changed_since(spawn, last_run))
+            .cloned());
+    }
+
+    fn update_system(&mut self, index: usize) {
+        let matches = match self.systems[index].system(&self.shared).only_changed() {
+            true => &self.changed[index],
+            false => self.matches[index].values(),
+        };
+        match &mut self.systems[index] {
+            Registered::Exclusive(system) => system.update_all(matches, &mut self.scene),
+            Registered::Shared(slot) => self.shared[*slot].system.update_shared(matches, &self.scene),
+        }
+    }
+
+    /// Disabled systems are skipped until they are enabled again, their matches are kept up to date in the meantime.
+    /// When a disabled system only updates changed objects, it is given all changes made while it was disabled.
+    /// 
+    pub fn set_system_enabled(&mut self, system: SystemId, enabled: bool) {
+        self.enabled[system.0] = enabled;
+    }
+
+    pub fn is_system_enabled(&self, system: SystemId) -> bool {
+        self.enabled[system.0]
+    }
+
+    /// Replace the run criteria of a system, see SystemOrder::run_if.
+    /// 
+    pub fn set_run_criteria(&mut self, system: SystemId, criteria: RunCriteria) {
+        self.criteria[system.0] = criteria;
+    }
+
+    /// Returns the first registered system of type S, if there is one.
+    /// 
+    pub fn system_id<S: 'static>(&self) -> Option<SystemId> {
+        self.types.iter().position(|type_id| *type_id == TypeId::of::<S>()).map(SystemId)
+    }
+
+    /// The order in which the systems are updated every frame, startup systems excluded.
+    /// 
+    pub fn schedule(&self) -> &[SystemId] {
+        &self.schedule
+    }
+
+    /// The systems that are updated at the same time, in the order the batches are updated,
+    /// startup systems excluded. See SharedSystem::access.
+    /// 
+    pub fn batches(&self) -> impl Iterator<Item = &[SystemId]> + '_ {
+        self.batches.iter().map(move |batch| &self.schedule[batch.clone()])
+    }
+
+    /// The current tick of the scene, see Scene::tick.
+    /// 
+    pub fn tick(&self) -> Tick {
+        self.scene.tick()
+    }
+
+    /// Borrow a global resource, see Scene::resource.
+    /// 
+    pub fn resource<R: Send + Sync + 'static>(&self) -> Result<Ref<'_, R>, SceneError> {
+        self.scene.resource::<R>()
+    }
+
+    /// Borrow a global resource mutably, see Scene::resource_mut.
+    /// 
+    pub fn resource_mut<R: Send + Sync + 'static>(&self) -> Result<RefMut<'_, R>, SceneError> {
+        self.scene.resource_mut::<R>()
+    }
+
+    pub fn time(&self) -> &Time {
+        self.scene.time()
+    }
+
+    /// The tick at which a system last ran, or 0 if it never did.
+    /// 
+    pub fn last_run(&self, system: SystemId) -> Tick {
+        self.last_run[system.0]
+    }
+
+    /// Apply the commands buffered by systems, move objects to their new position in the spatial index
+    /// and match the objects that changed to the systems again.
+    /// 
+    fn sync(&mut self) {
+        self.scene.apply_commands();
+        self.refresh_matches();
+    }
+
+    /// Returns the spawns of all objects that currently match the requirements of a system.
+    /// 
+    pub fn matches(&self, system: SystemId) -> &[Spawn] {
+        self.matches[system.0].values()
+    }
+
+    /// Returns the systems that would update the objects of a group, as they are built by the groups factory,
+    /// in the order they were registered.
+    /// 
+    pub fn affected_systems(&self, group: &Group) -> Result<Vec<SystemId>, SceneError> {
+        if *group >= self.scene.count_groups() { return Err(SceneError::GroupNotFound); }
+
+        let spawn = Spawn { group: *group, ..Spawn::default() };
+        let object = self.scene.get_factory(group).build(&spawn);
+
+        Ok(self.systems.iter().enumerate()
+            .filter(|(_, sys)| sys.system(&self.shared).matches(&object))
+            .map(|(index, _)| SystemId(index))
+            .collect())
+    }
+
+    /// Shut the systems down and take the scene out of the Ecs, to keep the objects after the systems are gone.
+    /// 
+    pub fn into_scene(mut self) -> Scene<E> {
+        self.shutdown();
+        // the Ecs is dropped after this, a scene without objects or worker threads takes the place of the real one
+        std::mem::replace(&mut self.scene, Scene::build(0, Vec::new(), None, 1))
+    }
+
+    /// Call the shutdown hooks in the order the systems were registered, the systems are dropped afterwards.
+    /// 
+    fn shutdown(&mut self) {
+        let mut shared = std::mem::take(&mut self.shared);
+        for mut sys in std::mem::take(&mut self.systems) {
+            sys.system_mut(&mut shared).shutdown(&mut self.scene);
+        }
+    }
+
+    /// Re-evaluate the requirements of all systems, for objects that have been changed,
+    /// and let the systems know which objects entered or exited their matches.
+    /// Columnar scenes have no whole objects to evaluate, so their objects are only matched by signature.
+    /// 
+    fn refresh_matches(&mut self) {
+        // called before and after every system, in most frames nothing has been touched
+        if self.scene.has_touched() { 
+            self.rematch_touched();
+        }
+    }
+
+    fn rematch_touched(&mut self) {
+        // objects changed in between updates are moved before the changes are cleared
+        self.scene.relocate_changed();
+
+        let columnar = self.scene.is_columnar();
+        let touched = self.scene.take_touched();
+        for (pointer, rematch) in touched.pointers().iter().zip(touched.values()) {
+            if !rematch { continue; }
+            let spawn = self.scene.spawn_at(pointer);
+            // objects stored without signature bits only match systems that require nothing
+            let signature = spawn.filter(|_| columnar).map(|spawn| self.scene.signature(spawn).unwrap_or_default());
+
+            for (index, (sys, matches)) in self.systems.iter().zip(&mut self.matches).enumerate() {
+                let matching = match (spawn, signature) {
+                    (Some(_), Some(signature)) => sys.system(&self.shared).matches_signature(signature),
+                    (Some(spawn), None) => sys.system(&self.shared).matches(&self.scene.get_ref(spawn)),
+                    (None, _) => false,
+                };
+                match spawn {
+                    Some(spawn) if matching => {
+                        match matches.insert(*pointer, spawn.clone()) {
+                            Some(previous) if previous == *spawn => {},
+                            // the slot has been reused by a new object since the last refresh
+                            Some(previous) => {
+                                self.transitions.push((index, previous, false));
+                                self.transitions.push((index, spawn.clone(), true));
+                            },
+                            None => self.transitions.push((index, spawn.clone(), true)),
+                        }
+                    },
+                    _ => { 
+                        if let Some(previous) = matches.remove(*pointer) {
+                            self.transitions.push((index, previous, false));
+                        }
+                    },
                 }
             }
         }
+        self.scene.clear_touched(touched);
+
+        for (index, spawn, entered) in self.transitions.drain(..) {
+            match entered {
+                true => self.systems[index].system_mut(&mut self.shared).on_enter(&spawn, &mut self.scene),
+                false => self.systems[index].system_mut(&mut self.shared).on_exit(&spawn, &mut self.scene),
+            }
+        }
+    }
+}
+
+impl<E: Entity> Drop for Ecs<E> {
+    fn drop(&mut self) {
+        // shutdown hooks could panic again while unwinding, which would abort
+        if !std::thread::panicking() {
+            self.shutdown();
+        }
     }
 }
 
 pub struct EcsBuilder<E: Entity> {
     pool_size: usize,
-    systems: Vec<Box::<dyn System<E>>>,
+    max_pool_size: Option<usize>,
+    growth: GrowthPolicy,
+    recycle: RecyclePolicy,
+    sync_point: SyncPoint,
+    timestep: Timestep,
+    threads: Option<usize>,
+    spatial_index: Option<Spatial<E>>,
+    columns: Option<fn(E, Pointer, &mut Columns)>,
+    resources: Resources,
+    systems: Vec<Registered<E>>,
+    shared: Vec<Shared<E>>,
+    entries: Vec<Entry>,
     factories: Vec<Box::<dyn Factory<E>>>,
 }
 
@@ -38,11 +463,97 @@ impl<E: Entity> EcsBuilder<E> {
     pub fn new(pool_size: usize) -> Self {
         EcsBuilder {
             pool_size,
+            max_pool_size: None,
+            growth: GrowthPolicy::Fixed,
+            recycle: RecyclePolicy::Keep,
+            sync_point: SyncPoint::EachSystem,
+            timestep: Timestep::Variable,
+            threads: None,
+            spatial_index: None,
+            columns: None,
+            resources: Resources::default(),
             systems: Vec::new(),
+            shared: Vec::new(),
+            entries: Vec::new(),
             factories: Vec::new(),
         }
     }
 
+    /// Let the pool grow when spawning while all objects are in use, 
+    /// by default the pool has a fixed size.
+    /// 
+    pub fn growth_policy(mut self, growth: GrowthPolicy) -> Self {
+        self.growth = growth;
+        self
+    }
+
+    /// Set a hard limit on the number of objects the pool can grow to.
+    /// 
+    pub fn max_pool_size(mut self, max_pool_size: usize) -> Self {
+        self.max_pool_size = Some(max_pool_size);
+        self
+    }
+
+    /// Set what happens to the objects of destroyed spawns, see Scene::set_recycle_policy.
+    /// 
+    pub fn recycle_policy(mut self, recycle: RecyclePolicy) -> Self {
+        self.recycle = recycle;
+        self
+    }
+
+    /// Set when buffered scene commands are applied during an update, 
+    /// by default they are applied after each system.
+    /// 
+    pub fn sync_point(mut self, sync_point: SyncPoint) -> Self {
+        self.sync_point = sync_point;
+        self
+    }
+
+    /// Update the systems 'hz' times per second, instead of once per update call. 
+    /// When an update call has more time to catch up on than 'max_steps' steps, the remaining time is dropped.
+    /// 
+    pub fn fixed_timestep(mut self, hz: f64, max_steps: u32) -> Self {
+        assert!(hz > 0.0, "fixed timestep must run more than zero times per second");
+        assert!(max_steps > 0, "fixed timestep must allow at least one step per update");
+        self.timestep = Timestep::Fixed { hz, max_steps };
+        self
+    }
+
+    /// Set the number of threads systems can be updated on at the same time,
+    /// by default as many as the machine can run in parallel. See SharedSystem::access and Scene::par_for_each.
+    /// 
+    pub fn threads(mut self, threads: usize) -> Self {
+        assert!(threads > 0, "the systems need at least one thread to run on");
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Keep track of where objects are positioned with a spatial index, see Scene::set_spatial_index.
+    /// 
+    pub fn spatial_index<I>(mut self, locate: fn(&E) -> Point, index: I) -> Self
+        where I: SpatialIndex + 'static
+    {
+        self.spatial_index = Some(Spatial { locate, index: Box::new(index) });
+        self
+    }
+
+    /// Store objects per component type, instead of storing whole objects, see Scene::use_columns.
+    /// 
+    pub fn columnar(mut self) -> Self
+        where E: Columnar
+    {
+        self.columns = Some(E::into_columns);
+        self
+    }
+
+    /// Add a global resource, that systems can access through Scene::resource and Scene::resource_mut.
+    /// There can be one resource of every type, inserting a resource of the same type twice replaces the first.
+    /// 
+    pub fn insert_resource<R: Send + Sync + 'static>(mut self, resource: R) -> Self {
+        self.resources.insert(resource);
+        self
+    }
+
     pub fn add_factory<F> (mut self, factory: F) -> Self
     where F: Factory<E> + 'static
     {
@@ -50,21 +561,149 @@ impl<E: Entity> EcsBuilder<E> {
         self
     }
 
-    pub fn register_system<S>(mut self, system: S) -> Self 
+    /// Add a system to the Update stage, see register_system_in.
+    /// 
+    pub fn register_system<S>(self, system: S) -> Self 
+    where S: System<E> + 'static
+    {
+        self.register_system_in(system, SystemOrder::default())
+    }
+
+    /// Same as register_system, but the id of the system is returned together with the builder,
+    /// so it can be enabled and disabled later on, see Ecs::set_system_enabled.
+    /// 
+    /// ```ignore
+    /// let (builder, movement) = EcsBuilder::new(10).register_system_with_id(MoveSystem);
+    /// let mut ecs = builder.register_system(AttackSystem).build();
+    /// ecs.set_system_enabled(movement, false);
+    /// ```
+    /// 
+    pub fn register_system_with_id<S>(mut self, system: S) -> (Self, SystemId)
+    where S: System<E> + 'static
+    {
+        let id = self.push_system::<S>(Registered::Exclusive(Box::new(system)), SystemOrder::default());
+        (self, id)
+    }
+
+    /// Add a system to a stage, to be updated before or after other systems as told by the order.
+    /// Systems without ordering constraints between them are updated in the order they were registered.
+    /// 
+    pub fn register_system_in<S>(mut self, system: S, order: SystemOrder) -> Self 
     where S: System<E> + 'static
     {
-        self.systems.push(Box::new(system));
+        self.push_system::<S>(Registered::Exclusive(Box::new(system)), order);
+        self
+    }
+
+    /// Add a system to the Update stage, that can be updated at the same time as other shared systems
+    /// it doesn't conflict with, see SharedSystem.
+    /// 
+    pub fn register_shared_system<S>(self, system: S) -> Self 
+    where S: SharedSystem<E> + 'static
+    {
+        self.register_shared_system_in(system, SystemOrder::default())
+    }
+
+    /// Same as register_system_in, for shared systems.
+    /// 
+    pub fn register_shared_system_in<S>(mut self, system: S, order: SystemOrder) -> Self 
+    where S: SharedSystem<E> + 'static
+    {
+        self.push_shared_system(system, order);
         self
     }
 
-    pub fn build(mut self) -> Ecs<E> {
+    /// Same as register_shared_system, but the id of the system is returned together with the builder,
+    /// see register_system_with_id.
+    /// 
+    pub fn register_shared_system_with_id<S>(mut self, system: S) -> (Self, SystemId)
+    where S: SharedSystem<E> + 'static
+    {
+        let id = self.push_shared_system(system, SystemOrder::default());
+        (self, id)
+    }
+
+    fn push_shared_system<S: SharedSystem<E> + 'static>(&mut self, system: S, order: SystemOrder) -> SystemId {
+        self.shared.push(Shared { index: self.systems.len(), system: Box::new(system) });
+        self.push_system::<S>(Registered::Shared(self.shared.len() - 1), order)
+    }
+
+    fn push_system<S: 'static>(&mut self, system: Registered<E>, order: SystemOrder) -> SystemId {
+        self.systems.push(system);
+        self.entries.push(Entry::of::<S>(order));
+        SystemId(self.systems.len() - 1)
+    }
+
+    /// Build the Ecs, panics if the systems can not be put in order, see try_build.
+    /// 
+    pub fn build(self) -> Ecs<E> {
+        match self.try_build() {
+            Ok(ecs) => ecs,
+            Err(error) => panic!("failed to schedule systems: {}", error),
+        }
+    }
+
+    /// Build the Ecs, fails if the ordering constraints of the systems contradict eachother.
+    /// 
+    pub fn try_build(mut self) -> Result<Ecs<E>, ScheduleError> {
+        let (startup, schedule) = schedule::resolve(&self.entries)?;
+
         for i in 0..self.factories.len() { 
             self.factories[i].init(i); 
         }
-        Ecs { 
-            scene: Scene::new(self.pool_size, self.factories),
-            systems: self.systems
+        let threads = self.threads.unwrap_or_else(scene::default_threads);
+        let mut scene = Scene::build(self.pool_size, self.factories, self.columns, threads);
+        scene.set_growth_policy(self.growth, self.max_pool_size);
+        scene.set_recycle_policy(self.recycle);
+        if let Some(spatial) = self.spatial_index {
+            scene.set_spatial(spatial);
+        }
+        scene.set_resources(self.resources);
+
+        let shared = &self.shared;
+        let parallel: Vec<Parallel> = self.systems.iter()
+            .map(|sys| match sys {
+                Registered::Exclusive(sys) => Parallel { access: None, only_changed: sys.only_changed() },
+                Registered::Shared(slot) => {
+                    let sys = &shared[*slot].system;
+                    Parallel { access: Some(sys.access()), only_changed: sys.only_changed() }
+                },
+            })
+            .collect();
+        let startup_batches = schedule::batches(&startup, &self.entries, &parallel, scene.is_columnar());
+        let batches = schedule::batches(&schedule, &self.entries, &parallel, scene.is_columnar());
+
+        for sys in self.systems.iter_mut() {
+            sys.system_mut(&mut self.shared).init(&mut scene);
         }
+
+        let mut matches = Vec::new();
+        let pool_size = self.pool_size;
+        matches.resize_with(self.systems.len(), || SparseSet::with_capacity(pool_size));
+
+        Ok(Ecs { 
+            scene,
+            last_run: vec![0; self.systems.len()],
+            running: Vec::with_capacity(self.systems.len()),
+            changed: parallel.iter()
+                .map(|sys| Vec::with_capacity(if sys.only_changed { pool_size } else { 0 }))
+                .collect(),
+            systems: self.systems,
+            shared: self.shared,
+            matches,
+            transitions: Vec::with_capacity(pool_size),
+            types: self.entries.iter().map(|entry| entry.type_id).collect(),
+            enabled: vec![true; self.entries.len()],
+            criteria: self.entries.iter().map(|entry| entry.order.criteria.clone()).collect(),
+            startup: startup.into_iter().map(SystemId).collect(),
+            schedule: schedule.into_iter().map(SystemId).collect(),
+            startup_batches,
+            batches,
+            started: false,
+            sync_point: self.sync_point,
+            timestep: self.timestep,
+            accumulator: 0.0,
+        })
     }
 }
 
@@ -72,7 +711,19 @@ impl<E: Entity> EcsBuilder<E> {
 #[cfg(test)]
 mod tests {
 
-    use super::EcsBuilder;
+    use super::{ EcsBuilder, SceneError, GrowthPolicy, RecyclePolicy, SyncPoint, Destroyed, Scene, System, Component, Broadphase };
+    use super::spatial::{ Grid, SpatialIndex, distance_squared };
+    use super::spawns::{ Spawn, Group };
+    use super::example::components::{ GameObject, GameObjectComponent, Faction, Position, Movement, Attack, Health };
+    use super::{ Columnar, Factory, Entity, Signature, EventReader, Stage, SystemOrder, ScheduleError, Access, RunCriteria, SharedSystem };
+    use super::{ Ecs, SystemId };
+    use super::example::events::{ WeaponFired, Died };
+
+    use std::alloc::{ GlobalAlloc, Layout, System as SystemAllocator };
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::sync::{ Arc, Mutex };
+    use std::sync::atomic::{ AtomicUsize, Ordering };
     use super::example::factories::*;
     use super::example::systems::*;
 
@@ -94,12 +745,12 @@ mod tests {
         assert_eq!(ecs.scene.get_ref(&soldier).position.x, 0.0);
         assert_eq!(ecs.scene.get_ref(&truck).position.x, 0.0);
 
-        ecs.update();
+        ecs.update(1.0);
 
         assert_eq!(ecs.scene.get_ref(&soldier).position.x, 1.0);
         assert_eq!(ecs.scene.get_ref(&truck).position.x, 2.0);
 
-        ecs.update();
+        ecs.update(1.0);
 
         assert_eq!(ecs.scene.get_ref(&soldier).position.x, 2.0);
         assert_eq!(ecs.scene.get_ref(&truck).position.x, 4.0);
@@ -107,6 +758,7 @@ mod tests {
 
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn destroy() {
         let soldiers = Soldier::new();
         let trucks = Truck::new();
@@ -123,7 +775,7 @@ mod tests {
         assert_eq!(ecs.scene.exists(&soldier), true);
         assert_eq!(ecs.scene.exists(&truck), true);
 
-        ecs.update();
+        ecs.update(1.0);
 
         assert_eq!(ecs.scene.get_ref(&soldier).position.x, 1.0);
         assert_eq!(ecs.scene.get_ref(&truck).position.x, 2.0);
@@ -133,36 +785,1500 @@ mod tests {
         assert_eq!(ecs.scene.exists(&soldier), false);
         assert_eq!(ecs.scene.exists(&truck), true);
 
-        ecs.update();
+        ecs.update(1.0);
 
         assert_eq!(ecs.scene.get_ref(&soldier).position.x, 1.0);
         assert_eq!(ecs.scene.get_ref(&truck).position.x, 4.0);
     }
 
     #[test]
-    fn speed() {
-        let soldiers = Soldier::new();
+    fn stale_spawn() {
+        let mut ecs = EcsBuilder::new(1)
+            .add_factory(Soldier::new())
+            .add_factory(Truck::new())
+            .build();
+        
+        let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+        ecs.scene.destroy(&soldier);
+        let truck = ecs.scene.spawn("Demo truck", &1).unwrap();
 
-        let mut ecs = EcsBuilder::new(1000)
-            .add_factory(soldiers)
-            .register_system(MoveSystem)
+        assert_eq!(soldier.pointer(), truck.pointer());
+        assert!(soldier != truck);
+        assert!(!ecs.scene.exists(&soldier));
+        assert!(!ecs.scene.exists_in_group(&soldier, 1));
+        assert!(!ecs.scene.is_current(&soldier));
+        assert!(ecs.scene.exists(&truck));
+
+        ecs.scene.destroy(&soldier);
+
+        assert!(ecs.scene.exists(&truck));
+    }
+
+    #[test]
+    fn destroy_missing() {
+        let mut ecs = EcsBuilder::new(1)
+            .add_factory(Soldier::new())
+            .build();
+
+        let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+        assert_eq!(ecs.scene.destroy(&soldier), Destroyed::Now);
+        assert_eq!(ecs.scene.destroy(&soldier), Destroyed::Missing);
+
+        let recruit = ecs.scene.spawn("Recruit", &0).unwrap();
+        assert_eq!(ecs.scene.destroy(&soldier), Destroyed::Missing);
+        assert!(ecs.scene.exists(&recruit));
+        assert_eq!(ecs.scene.destroy_group(1), Destroyed::Missing);
+
+        // a reserved spawn is destroyed once it has been spawned
+        ecs.scene.destroy(&recruit);
+        let reserved = ecs.scene.defer_spawn("Reserved", &0).unwrap();
+        assert_eq!(ecs.scene.destroy(&reserved), Destroyed::Deferred);
+        ecs.scene.apply_commands();
+        assert!(!ecs.scene.exists(&reserved));
+        assert_eq!(ecs.scene.count_in_group(0), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn stale_spawn_get_ref() {
+        let mut ecs = EcsBuilder::new(1)
+            .add_factory(Soldier::new())
+            .add_factory(Truck::new())
             .build();
         
         let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+        ecs.scene.destroy(&soldier);
+        ecs.scene.spawn("Demo truck", &1).unwrap();
 
-        assert_eq!(ecs.scene.get_ref(&soldier).position.x, 0.0);
+        ecs.scene.get_ref(&soldier);
+    }
+
+    #[test]
+    fn try_get() {
+        let mut ecs = EcsBuilder::new(1)
+            .add_factory(Soldier::new())
+            .add_factory(Truck::new())
+            .build();
+        
+        let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+
+        {
+            let _target = ecs.scene.try_get_mut(&soldier).unwrap();
+            assert_eq!(ecs.scene.try_get_ref(&soldier).err(), Some(SceneError::AlreadyBorrowed));
+            assert_eq!(ecs.scene.try_get_mut(&soldier).err(), Some(SceneError::AlreadyBorrowed));
+        }
+        {
+            let _target = ecs.scene.try_get_ref(&soldier).unwrap();
+            assert!(ecs.scene.try_get_ref(&soldier).is_ok());
+            assert_eq!(ecs.scene.try_get_mut(&soldier).err(), Some(SceneError::AlreadyBorrowed));
+        }
 
+        ecs.scene.destroy(&soldier);
+        assert_eq!(ecs.scene.try_get_ref(&soldier).err(), Some(SceneError::Destroyed));
 
-        for num_tries in 0..5 {
+        ecs.scene.spawn("Demo truck", &1).unwrap();
+        assert_eq!(ecs.scene.try_get_mut(&soldier).err(), Some(SceneError::StaleHandle));
+        assert_eq!(SceneError::StaleHandle.to_string(), "spawn refers to a slot that has been reused by a newer spawn");
+    }
 
-            let updates_per_second: u128 = 36_000_000;
+    #[test]
+    fn destroy_many() {
+        let mut ecs = EcsBuilder::new(100)
+            .add_factory(Soldier::new())
+            .add_factory(Truck::new())
+            .build();
 
-            let now = std::time::SystemTime::now();
-            for _i in 0..updates_per_second { ecs.update(); }
-            let elapsed = now.elapsed().unwrap().as_millis();
+        let soldiers: Vec<_> = (0..100)
+            .map(|_| ecs.scene.spawn("Private first", &0).unwrap())
+            .collect();
 
-            assert!(elapsed < 1_000, "duration should be shorter than 1 second, elapsed time was '{}' seconds", elapsed as f64 / 1_000.0);
-            assert_eq!(ecs.scene.get_ref(&soldier).position.x, (num_tries as f64 + 1.0) * updates_per_second as f64);
+        for soldier in soldiers.iter().step_by(2) {
+            ecs.scene.destroy(soldier);
+        }
+
+        assert_eq!(ecs.scene.list_spawned().len(), 50);
+        assert!(ecs.scene.search_components_in_group(1, |_| true).is_none());
+
+        for (i, soldier) in soldiers.iter().enumerate() {
+            assert_eq!(ecs.scene.exists(soldier), i % 2 == 1);
+            assert_eq!(ecs.scene.exists_in_group(soldier, 0), i % 2 == 1);
+            assert!(!ecs.scene.exists_in_group(soldier, 1));
+        }
+    }
+
+    #[test]
+    fn grow_and_shrink() {
+        let mut ecs = EcsBuilder::new(2)
+            .growth_policy(GrowthPolicy::Double)
+            .max_pool_size(6)
+            .add_factory(Soldier::new())
+            .build();
+
+        let soldiers: Vec<_> = (0..6)
+            .map(|_| ecs.scene.spawn("Private first", &0).unwrap())
+            .collect();
+
+        assert_eq!(ecs.scene.size(), 6);
+        assert_eq!(ecs.scene.spawn("Private first", &0).err(), Some(SceneError::Overflow));
+
+        for soldier in &soldiers[1..] {
+            ecs.scene.destroy(soldier);
+        }
+        ecs.scene.shrink_to_fit();
+
+        assert_eq!(ecs.scene.size(), 2);
+        assert!(ecs.scene.exists(&soldiers[0]));
+        assert_eq!(ecs.scene.get_ref(&soldiers[0]).position.x, 0.0);
+
+        let first = ecs.scene.spawn("Private first", &0).unwrap();
+        let second = ecs.scene.spawn("Private first", &0).unwrap();
+
+        assert_eq!(ecs.scene.size(), 4);
+        assert_eq!(first.pointer(), soldiers[1].pointer());
+        assert_eq!(second.pointer(), soldiers[2].pointer());
+        assert!(ecs.scene.exists_in_group(&second, 0));
+        assert!(!ecs.scene.exists(&soldiers[2]));
+        assert!(!ecs.scene.is_current(&soldiers[2]));
+    }
+
+    #[test]
+    fn chunk_growth() {
+        let mut ecs = EcsBuilder::new(0)
+            .growth_policy(GrowthPolicy::Chunk(10))
+            .add_factory(Truck::new())
+            .build();
+
+        for _i in 0..25 { ecs.scene.spawn("Demo truck", &0).unwrap(); }
+
+        assert_eq!(ecs.scene.size(), 30);
+    }
+
+    struct Recruiter;
+
+    impl System<GameObject> for Recruiter {
+        fn requirements(&self, target: &GameObject) -> bool {
+            target.has_movement()
+        }
+        fn update(&mut self, spawn: &Spawn, scene: &mut Scene<GameObject>) {
+            scene.defer_destroy(spawn);
+            scene.defer_spawn("Recruit", &0).unwrap();
+        }
+    }
+
+    #[test]
+    fn deferred_commands() {
+        let mut ecs = EcsBuilder::new(10)
+            .add_factory(Soldier::new())
+            .build();
+
+        let recruit = ecs.scene.defer_spawn("Recruit", &0).unwrap();
+
+        assert!(!ecs.scene.exists(&recruit));
+        assert_eq!(ecs.scene.try_get_ref(&recruit).err(), Some(SceneError::Reserved));
+        
+        ecs.scene.defer_modify(&recruit, |target| target.position.x = 5.0).unwrap();
+        ecs.scene.apply_commands();
+
+        assert!(ecs.scene.exists(&recruit));
+        assert_eq!(ecs.scene.get_ref(&recruit).position.x, 5.0);
+
+        ecs.scene.defer_destroy(&recruit);
+        assert!(ecs.scene.exists(&recruit));
+
+        ecs.scene.apply_commands();
+        assert!(!ecs.scene.exists(&recruit));
+        assert_eq!(ecs.scene.pending_commands(), 0);
+    }
+
+    #[test]
+    fn sync_points() {
+        for sync_point in [SyncPoint::EachSystem, SyncPoint::EndOfFrame] {
+            let mut ecs = EcsBuilder::new(10)
+                .sync_point(sync_point)
+                .add_factory(Soldier::new())
+                .register_system(Recruiter)
+                .register_system(MoveSystem)
+                .build();
+
+            let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+            ecs.update(1.0);
+
+            let recruit = ecs.scene.list_spawned()[0].clone();
+            let moved = ecs.scene.get_ref(&recruit).position.x;
+
+            assert!(!ecs.scene.exists(&soldier));
+            assert_eq!(ecs.scene.list_spawned().len(), 1);
+            assert_eq!(ecs.scene.pending_commands(), 0);
+            assert_eq!(moved, if sync_point == SyncPoint::EachSystem { 1.0 } else { 0.0 });
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn groups() {
+        let mut ecs = EcsBuilder::new(20)
+            .add_factory(Soldier::new())
+            .add_factory(Truck::new())
+            .build();
+
+        for _i in 0..3 { ecs.scene.spawn("Private first", &0).unwrap(); }
+        let trucks: Vec<_> = (0..5)
+            .map(|_| ecs.scene.spawn("Demo truck", &1).unwrap())
+            .collect();
+        ecs.scene.destroy(&trucks[0]);
+
+        assert_eq!(ecs.scene.count_in_group(0), 3);
+        assert_eq!(ecs.scene.count_in_group(1), 4);
+        assert_eq!(ecs.scene.count_in_group(2), 0);
+        assert!(ecs.scene.iter_group(1).all(|spawn| *spawn.group() == 1 && ecs.scene.exists(spawn)));
+        assert!(!ecs.scene.iter_group(1).any(|spawn| *spawn == trucks[0]));
+        assert_eq!(ecs.scene.spawned_in_groups(&[0, 1]).count(), 7);
+
+        let allocated = allocations();
+        assert_eq!(ecs.scene.iter_group(0).count(), 3);
+        assert_eq!(allocations() - allocated, 0);
+
+        ecs.scene.destroy_group(1);
+
+        assert_eq!(ecs.scene.count_in_group(1), 0);
+        assert_eq!(ecs.scene.spawned().len(), 3);
+        assert!(trucks.iter().all(|truck| !ecs.scene.exists(truck)));
+    }
+
+    #[test]
+    fn names() {
+        let mut ecs = EcsBuilder::new(10)
+            .add_factory(Soldier::new())
+            .add_factory(Truck::new())
+            .build();
+
+        let long_name = "squad_alpha/private_first/with_a_name_longer_than_sixteen_bytes";
+        let private = ecs.scene.spawn(long_name, &0).unwrap();
+        let mate = ecs.scene.spawn(long_name, &0).unwrap();
+        let truck = ecs.scene.spawn("vrachtwagen/ÿ€", &1).unwrap();
+
+        assert_eq!(private.name(), long_name);
+        assert_eq!(private.name().as_ptr(), mate.name().as_ptr());
+        assert_eq!(truck.name(), "vrachtwagen/ÿ€");
+        assert_eq!(ecs.scene.count_named(long_name), 2);
+        assert!(ecs.scene.find_spawn("vrachtwagen/ÿ€") == Some(truck.clone()));
+        assert!(ecs.scene.find_spawn_in_group(long_name, 1).is_none());
+        assert!(ecs.scene.find_spawn("squad_alpha").is_none());
+
+        ecs.scene.destroy(&private);
+        assert!(ecs.scene.find_spawn(long_name) == Some(mate.clone()));
+        assert!(ecs.scene.find_spawn_in_group(long_name, 0) == Some(mate.clone()));
+
+        ecs.scene.destroy(&mate);
+        assert!(ecs.scene.find_spawn(long_name).is_none());
+        assert_eq!(ecs.scene.count_named(long_name), 0);
+    }
+
+    #[test]
+    fn queries() {
+        let mut ecs = EcsBuilder::new(10)
+            .add_factory(Soldier::new())
+            .add_factory(Truck::new())
+            .build();
+
+        for x in [4.0, 1.0, 3.0] {
+            let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+            ecs.scene.get_mut(&soldier).position.x = x;
+        }
+        let truck = ecs.scene.spawn("Demo truck", &1).unwrap();
+        ecs.scene.get_mut(&truck).position.x = 2.0;
+
+        assert_eq!(ecs.scene.query(|other| other.position.x > 1.5).count(), 3);
+        assert_eq!(ecs.scene.query_in_group(0, |other| other.position.x > 1.5).count(), 2);
+        assert_eq!(ecs.scene.query(|_| true).take(2).count(), 2);
+        assert_eq!(ecs.scene.find_all("Private first").count(), 3);
+
+        let sorted: Vec<f64> = ecs.scene.query_sorted_by_key(|_| true, |other| other.position.x)
+            .iter()
+            .map(|(_, other)| other.position.x)
+            .collect();
+        assert_eq!(sorted, vec![1.0, 2.0, 3.0, 4.0]);
+
+        for (_, mut other) in ecs.scene.query_in_group_mut(0, |_| true) {
+            other.position.y = 1.0;
+        }
+        assert_eq!(ecs.scene.query(|other| other.position.y == 1.0).count(), 3);
+
+        // objects that are borrowed mutably, are skipped
+        let _target = ecs.scene.get_mut(&truck);
+        assert_eq!(ecs.scene.query(|_| true).count(), 3);
+        assert_eq!(ecs.scene.query_mut(|_| true).count(), 3);
+    }
+
+    #[test]
+    fn attack() {
+        for spatial in [false, true] {
+            let mut builder = EcsBuilder::new(10)
+                .add_factory(Soldier::new())
+                .add_factory(Truck::new())
+                .register_system(AttackSystem);
+            if spatial {
+                builder = builder.spatial_index(GameObject::location, Grid::new(10.0));
+            }
+            let mut ecs = builder.build();
+
+            let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+            let truck = ecs.scene.spawn("Demo truck", &1).unwrap();
+            let other_truck = ecs.scene.spawn("Demo truck", &1).unwrap();
+            ecs.scene.get_mut(&soldier).agenda.faction = Faction::Red;
+            ecs.scene.get_mut(&truck).agenda.faction = Faction::Bleu;
+            ecs.scene.get_mut(&other_truck).agenda.faction = Faction::Bleu;
+
+            // a soldier focuses on one opponent at a time
+            ecs.update(1.0);
+            assert_eq!(ecs.scene.get_ref(&soldier).focus.count(), 1);
+            assert!(ecs.scene.get_ref(&soldier).focus.prime() == Some(&truck));
+
+            ecs.update(1.0);
+            assert!(ecs.scene.get_mut(&truck).damage.next().is_some());
+
+            ecs.scene.destroy(&truck);
+            ecs.update(1.0);
+            assert_eq!(ecs.scene.get_ref(&soldier).focus.count(), 0);
+        }
+    }
+
+    #[test]
+    fn spatial_grid() {
+        let mut grid = Grid::new(10.0);
+        let mut points = Vec::new();
+        let mut seed: u64 = 7;
+        let mut random = move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as f64 / (1u64 << 31) as f64 * 200.0 - 100.0
+        };
+
+        for pointer in 0..500 {
+            let point = (random(), random());
+            grid.insert(pointer, point);
+            points.push(point);
+        }
+        for pointer in (0..500).step_by(3) {
+            points[pointer] = (random(), random());
+            grid.insert(pointer, points[pointer]);
+        }
+        grid.remove(1);
+
+        let center = (12.5, -30.0);
+        let mut found = Vec::new();
+        grid.within_radius(center, 25.0, &mut |pointer, _| found.push(pointer));
+        found.sort();
+        let expected: Vec<usize> = (0..500)
+            .filter(|pointer| *pointer != 1 && distance_squared(center, points[*pointer]) <= 25.0 * 25.0)
+            .collect();
+        assert_eq!(found, expected);
+
+        let mut found = Vec::new();
+        grid.within_rect((-50.0, 0.0), (0.0, 75.0), &mut |pointer, _| found.push(pointer));
+        found.sort();
+        let expected: Vec<usize> = (0..500)
+            .filter(|pointer| *pointer != 1)
+            .filter(|pointer| { 
+                let (x, y) = points[*pointer]; 
+                (-50.0..=0.0).contains(&x) && (0.0..=75.0).contains(&y) 
+            })
+            .collect();
+        assert_eq!(found, expected);
+
+        let mut everywhere = 0;
+        grid.within_rect((f64::NEG_INFINITY, f64::MIN), (f64::INFINITY, f64::MAX), &mut |_, _| everywhere += 1);
+        assert_eq!(everywhere, 499);
+
+        for k in [1, 5, 50, 600] {
+            let mut expected: Vec<usize> = (0..500).filter(|pointer| *pointer != 1).collect();
+            expected.sort_by(|a, b| {
+                distance_squared(center, points[*a]).partial_cmp(&distance_squared(center, points[*b])).unwrap()
+            });
+            expected.truncate(k);
+            assert_eq!(grid.k_nearest(center, k), expected);
+        }
+    }
+
+    #[test]
+    fn spatial_scene() {
+        let mut ecs = EcsBuilder::new(10)
+            .add_factory(Soldier::new())
+            .add_factory(Truck::new())
+            .register_system(MoveSystem)
+            .spatial_index(GameObject::location, Grid::new(5.0))
+            .build();
+
+        let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+        let truck = ecs.scene.spawn("Demo truck", &1).unwrap();
+        ecs.scene.get_mut(&truck).position.y = 20.0;
+        ecs.scene.touch(&truck);
+
+        assert_eq!(ecs.scene.within_radius((0.0, 0.0), 1.0).unwrap().len(), 1);
+        assert!(ecs.scene.k_nearest((0.0, 19.0), 1).unwrap()[0] == truck);
+        assert_eq!(ecs.scene.within_rect((-1.0, -1.0), (1.0, 21.0)).unwrap().len(), 2);
+
+        for _i in 0..3 { ecs.update(1.0); }
+
+        // soldier moved to x = 3, truck to x = 6
+        assert!(ecs.scene.within_radius((3.0, 0.0), 0.5).unwrap() == vec![soldier.clone()]);
+        assert!(ecs.scene.within_rect((5.0, 19.0), (7.0, 21.0)).unwrap() == vec![truck.clone()]);
+
+        ecs.scene.destroy(&soldier);
+        assert!(ecs.scene.within_radius((3.0, 0.0), 0.5).unwrap().is_empty());
+
+        let mut plain = EcsBuilder::new(10).add_factory(Soldier::new()).build();
+        plain.scene.spawn("Private first", &0).unwrap();
+        assert_eq!(plain.scene.within_radius((0.0, 0.0), 1.0).err(), Some(SceneError::NoSpatialIndex));
+    }
+
+    #[test]
+    fn relocate_touched() {
+        let mut ecs = EcsBuilder::new(10)
+            .add_factory(Soldier::new())
+            .register_system(MoveSystem)
+            .spatial_index(GameObject::location, Grid::new(5.0))
+            .build();
+
+        let moved = ecs.scene.spawn("Private first", &0).unwrap();
+        let idle = ecs.scene.spawn("Private second", &0).unwrap();
+        ecs.update(1.0);
+        let moved_by_system = ecs.scene.take_touched();
+        ecs.scene.clear_touched(moved_by_system);
+
+        // only the changed object is relocated, the others are left alone
+        ecs.scene.get_mut(&moved).position.x = 12.0;
+        let _ = ecs.scene.get_mut(&idle);
+        let touched = ecs.scene.take_touched();
+        assert_eq!(touched.pointers(), &[moved.pointer]);
+        assert_eq!(touched.values(), &[false]);
+        ecs.scene.clear_touched(touched);
+
+        ecs.scene.get_mut(&moved).position.x = 12.0;
+        ecs.update(1.0);
+        // both soldiers moved one step along x
+        assert!(ecs.scene.within_radius((13.0, 0.0), 0.5).unwrap() == vec![moved.clone()]);
+        assert!(ecs.scene.within_radius((2.0, 0.0), 0.5).unwrap() == vec![idle.clone()]);
+    }
+
+    #[test]
+    fn pairs() {
+        let mut ecs = EcsBuilder::new(10)
+            .add_factory(Soldier::new())
+            .add_factory(Truck::new())
+            .spatial_index(GameObject::location, Grid::new(2.0))
+            .build();
+
+        for x in 0..4 {
+            let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+            ecs.scene.defer_modify(&soldier, move |target| target.position.x = x as f64 * 2.0).unwrap();
+        }
+        for _i in 0..2 { ecs.scene.spawn("Demo truck", &1).unwrap(); }
+        ecs.scene.apply_commands();
+
+        assert_eq!(ecs.scene.pairs().count(), 15);
+        assert!(ecs.scene.pairs().all(|(a, b)| a != b));
+
+        let mut count = 0;
+        ecs.scene.for_each_pair(Broadphase::All, |(_, a), (_, b)| {
+            a.position.y += 1.0;
+            b.position.y += 1.0;
+            count += 1;
+        }).unwrap();
+        assert_eq!(count, 15);
+        assert!(ecs.scene.spawned().iter().all(|spawn| ecs.scene.get_ref(spawn).position.y == 5.0));
+
+        let mut count = 0;
+        ecs.scene.for_each_pair(Broadphase::Groups(&[(0, 1)]), |(a, _), (b, _)| {
+            assert_eq!((*a.group(), *b.group()), (0, 1));
+            count += 1;
+        }).unwrap();
+        assert_eq!(count, 8);
+
+        let mut count = 0;
+        ecs.scene.for_each_pair(Broadphase::Groups(&[(0, 0)]), |_, _| count += 1).unwrap();
+        assert_eq!(count, 6);
+
+        // soldiers are 2.0 apart along the x axis, both trucks are at the position of the first soldier,
+        // so there are 3 pairs of soldiers, 4 pairs of a truck and a soldier and 1 pair of trucks
+        let mut count = 0;
+        ecs.scene.for_each_pair(Broadphase::Within(2.5), |_, _| count += 1).unwrap();
+        assert_eq!(count, 3 + 4 + 1);
+
+        let mut plain = EcsBuilder::new(10).add_factory(Soldier::new()).build();
+        assert_eq!(plain.scene.for_each_pair(Broadphase::Within(1.0), |_, _| ()).err(), Some(SceneError::NoSpatialIndex));
+    }
+
+    #[test]
+    fn cached_matches() {
+        let mut ecs = EcsBuilder::new(10)
+            .add_factory(Soldier::new())
+            .register_system(MoveSystem)
+            .build();
+
+        let movement = ecs.system_id::<MoveSystem>().unwrap();
+        let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+        ecs.update(1.0);
+
+        assert_eq!(ecs.matches(movement).len(), 1);
+        assert_eq!(ecs.scene.get_ref(&soldier).position.x, 1.0);
+
+        // requirements are not evaluated until the object is touched
+        ecs.scene.get_mut(&soldier).movement.set_active(false);
+        ecs.update(1.0);
+
+        assert_eq!(ecs.scene.get_ref(&soldier).position.x, 2.0);
+
+        ecs.scene.touch(&soldier);
+        ecs.update(1.0);
+
+        assert_eq!(ecs.matches(movement).len(), 0);
+        assert_eq!(ecs.scene.get_ref(&soldier).position.x, 2.0);
+
+        ecs.scene.defer_modify(&soldier, |target| target.movement.set_active(true)).unwrap();
+        ecs.scene.apply_commands();
+        ecs.update(1.0);
+
+        assert_eq!(ecs.scene.get_ref(&soldier).position.x, 3.0);
+
+        ecs.scene.destroy(&soldier);
+        ecs.update(1.0);
+
+        assert_eq!(ecs.matches(movement).len(), 0);
+    }
+
+    #[test]
+    fn columns() {
+        let mut ecs = EcsBuilder::new(10)
+            .columnar()
+            .add_factory(Soldier::new())
+            .add_factory(Truck::new())
+            .register_shared_system(ColumnMoveSystem)
+            .build();
+
+        let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+        let truck = ecs.scene.spawn("Truck", &1).unwrap();
+        ecs.update(1.0);
+
+        assert!(ecs.scene.is_columnar());
+        assert_eq!(ecs.scene.component::<Position>(&soldier).unwrap().x, 1.0);
+        assert_eq!(ecs.scene.column::<Position>().unwrap().len(), 2);
+        assert_eq!(ecs.scene.column::<Movement>().unwrap().len(), 2);
+
+        // trucks can not attack, so they have no place in the attack column
+        assert_eq!(ecs.scene.column::<Attack>().unwrap().len(), 1);
+        assert!(ecs.scene.component::<Attack>(&truck).is_none());
+
+        ecs.scene.component_mut::<Position>(&soldier).unwrap().x = 10.0;
+        ecs.update(1.0);
+        assert_eq!(ecs.scene.component::<Position>(&soldier).unwrap().x, 11.0);
+
+        // there are no whole objects to borrow, but components can be added to live objects
+        assert_eq!(ecs.scene.try_get_ref(&soldier).err(), Some(SceneError::Columnar));
+        assert_eq!(ecs.scene.query(|_| true).count(), 0);
+        assert_eq!(ecs.scene.defer_modify(&soldier, |_| {}), Err(SceneError::Columnar));
+        assert_eq!(ecs.matches(ecs.system_id::<ColumnMoveSystem>().unwrap()).len(), 2);
+
+        assert!(ecs.scene.insert_component(&truck, Faction::Red) == Ok(None));
+        assert!(*ecs.scene.component::<Faction>(&truck).unwrap() == Faction::Red);
+        assert!(ecs.scene.remove_component::<Faction>(&truck) == Ok(Some(Faction::Red)));
+        assert!(ecs.scene.column::<Faction>().unwrap().is_empty());
+
+        ecs.scene.destroy(&soldier);
+        assert!(ecs.scene.component::<Position>(&soldier).is_none());
+        assert_eq!(ecs.scene.column::<Position>().unwrap().len(), 1);
+        assert_eq!(ecs.scene.column::<Attack>().unwrap().len(), 0);
+
+        // switching to columns moves already spawned objects into the columns
+        let mut scene = Scene::new(10, vec![Box::new(Soldier::new())]);
+        let soldier = scene.spawn("Private first", &0).unwrap();
+        scene.use_columns(GameObject::into_columns);
+        assert_eq!(scene.column::<Health>().unwrap().get(*soldier.pointer()).unwrap().current_hp, 10);
+    }
+
+    #[test]
+    fn column_matches() {
+        let mut ecs = EcsBuilder::new(10)
+            .columnar()
+            .add_factory(Soldier::new())
+            .add_factory(Truck::new())
+            .register_system(ColumnMoveSystem)
+            .register_system(Unarmed)
+            .build();
+        let (movement, unarmed) = (ecs.system_id::<ColumnMoveSystem>().unwrap(), ecs.system_id::<Unarmed>().unwrap());
+
+        let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+        let truck = ecs.scene.spawn("Truck", &1).unwrap();
+        ecs.update(1.0);
+
+        // the columns keep the signature the object had as a whole
+        let built = Soldier::new().build(&soldier);
+        assert_eq!(ecs.scene.signature(&soldier), built.signature());
+        assert!(ecs.matches(movement) == [soldier.clone(), truck.clone()]);
+        assert!(ecs.matches(unarmed) == [truck.clone()]);
+        assert_eq!(ecs.scene.component::<Position>(&truck).unwrap().x, 2.0);
+
+        // removing a component matches the object again
+        ecs.scene.remove_component::<Movement>(&truck).unwrap();
+        ecs.update(1.0);
+        assert!(ecs.matches(movement) == [soldier.clone()]);
+        assert!(ecs.matches(unarmed).is_empty());
+        assert_eq!(ecs.scene.component::<Position>(&truck).unwrap().x, 2.0);
+        assert_eq!(ecs.scene.component::<Position>(&soldier).unwrap().x, 2.0);
+
+        ecs.scene.insert_component(&truck, Movement::from_speed(1.0)).unwrap();
+        ecs.update(1.0);
+        assert_eq!(ecs.matches(movement).len(), 2);
+        assert_eq!(ecs.scene.component::<Position>(&truck).unwrap().x, 3.0);
+
+        ecs.scene.destroy(&soldier);
+        ecs.update(1.0);
+        assert!(ecs.matches(movement) == [truck.clone()]);
+        assert!(ecs.matches(unarmed) == [truck.clone()]);
+    }
+
+    #[test]
+    fn derives() {
+        let soldier = Soldier::new().build(&Spawn::default());
+        let truck = Truck::new().build(&Spawn::default());
+
+        assert_eq!(GameObjectComponent::COUNT, 11);
+        assert_eq!(GameObjectComponent::ALL[0], GameObjectComponent::Position);
+        assert_eq!(GameObjectComponent::Afflictions.name(), "afflictions");
+        assert_eq!(GameObjectComponent::Agenda.bit(), 0b10);
+
+        assert!(soldier.has(GameObjectComponent::Attack) && soldier.has_attack());
+        assert!(!truck.has(GameObjectComponent::Attack) && !truck.has_attack());
+        assert_eq!(soldier.component_mask() & GameObjectComponent::Attack.bit(), GameObjectComponent::Attack.bit());
+        assert_eq!(truck.component_mask() & GameObjectComponent::Attack.bit(), 0);
+
+        let mut position = Position::inactive();
+        assert!(!position.is_active());
+        position.set_active(true);
+        assert!(position.is_active());
+
+        // raw identifiers keep their name, without the 'r#'
+        #[derive(Default, Clone, Entity)]
+        struct Cargo {
+            r#type: Position,
+        }
+        let cargo = Cargo { r#type: Position::default() };
+        assert_eq!(CargoComponent::Type.name(), "type");
+        assert_eq!(cargo.has_type(), *cargo.r#type.is_active());
+    }
+
+    struct Unarmed;
+
+    impl System<GameObject> for Unarmed {
+        fn required(&self) -> Signature { GameObjectComponent::Movement.into() }
+        fn excluded(&self) -> Signature { GameObjectComponent::Attack.into() }
+        fn update(&mut self, _spawn: &Spawn, _scene: &mut Scene<GameObject>) {}
+    }
+
+    #[test]
+    fn signatures() {
+        let mut ecs = EcsBuilder::new(10)
+            .add_factory(Soldier::new())
+            .add_factory(Truck::new())
+            .register_system(MoveSystem)
+            .register_system(AttackSystem)
+            .register_system(Unarmed)
+            .build();
+        let movement = ecs.system_id::<MoveSystem>().unwrap();
+        let attack = ecs.system_id::<AttackSystem>().unwrap();
+        let unarmed = ecs.system_id::<Unarmed>().unwrap();
+
+        let required = Signature::of([GameObjectComponent::Position, GameObjectComponent::Movement]);
+        assert_eq!(required.len(), 2);
+        assert!(required.contains(GameObjectComponent::Position.into()));
+        assert!(!required.intersects(GameObjectComponent::Attack.into()));
+
+        let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+        let truck = ecs.scene.spawn("Truck", &1).unwrap();
+        assert!(ecs.scene.get_ref(&truck).signature().unwrap().contains(required));
+        ecs.update(1.0);
+
+        assert_eq!(ecs.matches(movement).len(), 2);
+        assert!(ecs.matches(attack) == [soldier.clone()]);
+        assert!(ecs.matches(unarmed) == [truck]);
+
+        assert_eq!(ecs.affected_systems(&0), Ok(vec![movement, attack]));
+        assert_eq!(ecs.affected_systems(&1), Ok(vec![movement, unarmed]));
+        assert_eq!(ecs.affected_systems(&2), Err(SceneError::GroupNotFound));
+
+        // losing a component changes the signature
+        ecs.scene.get_mut(&soldier).attack.set_active(false);
+        ecs.scene.touch(&soldier);
+        ecs.update(1.0);
+
+        assert_eq!(ecs.matches(attack).len(), 0);
+        assert_eq!(ecs.matches(unarmed).len(), 2);
+    }
+
+    struct Medic { treated: Arc<AtomicUsize> }
+
+    impl System<GameObject> for Medic {
+        fn required(&self) -> Signature { GameObjectComponent::Health.into() }
+        fn only_changed(&self) -> bool { true }
+
+        fn update(&mut self, spawn: &Spawn, scene: &mut Scene<GameObject>) {
+            scene.get_mut(spawn).health.heal(1);
+            self.treated.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn change_detection() {
+        let treated = Arc::new(AtomicUsize::new(0));
+        let (builder, medic) = EcsBuilder::new(10)
+            .add_factory(Soldier::new())
+            .register_system_with_id(Medic { treated: treated.clone() });
+        let mut ecs = builder.build();
+
+        let start = ecs.tick();
+        let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+        let other = ecs.scene.spawn("Private second", &0).unwrap();
+        assert_eq!(ecs.scene.changed_since(start - 1).count(), 2);
+
+        // newly spawned objects count as changed
+        ecs.update(1.0);
+        assert_eq!(treated.swap(0, Ordering::Relaxed), 2);
+        assert_eq!(ecs.last_run(medic), start + 1);
+
+        // the medic does not see its own changes
+        ecs.update(1.0);
+        assert_eq!(treated.swap(0, Ordering::Relaxed), 0);
+        assert_eq!(ecs.scene.changed_since(ecs.tick()).count(), 0);
+
+        let before = ecs.tick();
+        ecs.scene.get_mut(&soldier).health.damage(5);
+        assert_eq!(ecs.scene.get_mut(&other).health.current_hp, 10);
+        assert!(ecs.scene.is_changed_since(&soldier, before - 1));
+        assert!(!ecs.scene.is_changed_since(&other, before - 1));
+        assert_eq!(ecs.scene.changed_at(&soldier), Some(before));
+
+        ecs.update(1.0);
+        assert_eq!(treated.swap(0, Ordering::Relaxed), 1);
+        assert_eq!(ecs.scene.get_ref(&soldier).health.current_hp, 6);
+
+        ecs.scene.get_mut(&other).bypass_change_detection().health.damage(5);
+        ecs.scene.defer_modify(&soldier, |target| target.health.damage(1)).unwrap();
+        ecs.scene.apply_commands();
+        ecs.update(1.0);
+        assert_eq!(treated.swap(0, Ordering::Relaxed), 1);
+
+        ecs.scene.destroy(&soldier);
+        assert_eq!(ecs.scene.changed_at(&soldier), None);
+    }
+
+    struct Reporter { 
+        fired: EventReader<WeaponFired>,
+        reports: Arc<AtomicUsize>,
+    }
+
+    impl System<GameObject> for Reporter {
+        fn requirements(&self, _target: &GameObject) -> bool { false }
+        fn update(&mut self, _spawn: &Spawn, _scene: &mut Scene<GameObject>) {}
+
+        fn update_all(&mut self, _matches: &[Spawn], scene: &mut Scene<GameObject>) {
+            let count = scene.read(&mut self.fired).count();
+            self.reports.fetch_add(count, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn events() {
+        let mut scene = Scene::new(1, vec![Box::new(Soldier::new())]);
+        let mut early = EventReader::<u32>::new();
+        assert_eq!(scene.read(&mut early).count(), 0);
+
+        scene.send(1u32);
+        scene.send(2u32);
+        assert_eq!(scene.read(&mut early).count(), 0);
+        scene.apply_events();
+        assert_eq!(scene.read(&mut early).next(), Some(&1));
+        assert_eq!(scene.read(&mut early).copied().collect::<Vec<_>>(), vec![2]);
+        assert_eq!(scene.read(&mut early).count(), 0);
+
+        // events stay readable during the next frame
+        scene.update_events();
+        scene.send(3u32);
+        scene.apply_events();
+        let mut late = EventReader::<u32>::new();
+        assert_eq!(scene.read(&mut late).copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(scene.events::<u32>().unwrap().unread(&early), 1);
+        assert_eq!(scene.read(&mut early).copied().collect::<Vec<_>>(), vec![3]);
+
+        // and are dropped after that
+        scene.update_events();
+        scene.update_events();
+        assert!(scene.events::<u32>().unwrap().is_empty());
+        scene.send(4u32);
+        scene.apply_events();
+        assert_eq!(scene.read(&mut EventReader::<u32>::new()).copied().collect::<Vec<_>>(), vec![4]);
+
+        let reports = Arc::new(AtomicUsize::new(0));
+        let mut ecs = EcsBuilder::new(10)
+            .add_factory(Soldier::new())
+            .add_factory(Truck::new())
+            .register_system(Reporter { fired: EventReader::new(), reports: reports.clone() })
+            .register_system(AttackSystem)
+            .register_system(DamageSystem)
+            .build();
+
+        let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+        let truck = ecs.scene.spawn("Demo truck", &1).unwrap();
+        ecs.scene.get_mut(&soldier).agenda.faction = Faction::Red;
+        ecs.scene.get_mut(&truck).agenda.faction = Faction::Bleu;
+
+        // the reporter runs before the attack system, so it reads the shots of the previous frame
+        ecs.update(1.0);
+        ecs.update(1.0);
+        assert_eq!(reports.load(Ordering::Relaxed), 0);
+        ecs.update(1.0);
+        assert_eq!(reports.load(Ordering::Relaxed), 1);
+
+        let mut deaths = EventReader::<Died>::new();
+        let mut died = Vec::new();
+        for _frame in 0..30 { 
+            ecs.update(1.0); 
+            died.extend(ecs.scene.read(&mut deaths).map(|Died(spawn)| spawn.clone()));
+        }
+        assert_eq!(ecs.scene.get_ref(&truck).health.current_hp, 0);
+        assert!(died == [truck]);
+    }
+
+    struct Frames(u32);
+    struct Settings { speed_boost: f64 }
+
+    struct Accountant;
+
+    impl System<GameObject> for Accountant {
+        fn required(&self) -> Signature { GameObjectComponent::Position.into() }
+
+        fn update(&mut self, spawn: &Spawn, scene: &mut Scene<GameObject>) {
+            let boost = scene.resource::<Settings>().unwrap().speed_boost;
+            scene.get_mut(spawn).position.x += boost;
+        }
+
+        fn update_all(&mut self, matches: &[Spawn], scene: &mut Scene<GameObject>) {
+            scene.resource_mut::<Frames>().unwrap().0 += 1;
+            for spawn in matches { self.update(spawn, scene); }
+        }
+    }
+
+    #[test]
+    fn resources() {
+        let mut ecs = EcsBuilder::new(10)
+            .add_factory(Soldier::new())
+            .insert_resource(Frames(0))
+            .insert_resource(Settings { speed_boost: 0.5 })
+            .register_system(Accountant)
+            .build();
+
+        let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+        ecs.update(1.0);
+        ecs.update(1.0);
+
+        assert_eq!(ecs.resource::<Frames>().unwrap().0, 2);
+        assert_eq!(ecs.scene.get_ref(&soldier).position.x, 1.0);
+
+        ecs.resource_mut::<Settings>().unwrap().speed_boost = 2.0;
+        ecs.update(1.0);
+        assert_eq!(ecs.scene.get_ref(&soldier).position.x, 3.0);
+
+        assert_eq!(ecs.resource::<String>().err(), Some(SceneError::ResourceNotFound));
+        {
+            let _frames = ecs.resource_mut::<Frames>().unwrap();
+            assert_eq!(ecs.resource::<Frames>().err(), Some(SceneError::AlreadyBorrowed));
+            assert_eq!(ecs.resource_mut::<Frames>().err(), Some(SceneError::AlreadyBorrowed));
+            assert!(ecs.resource::<Settings>().is_ok());
+        }
+
+        assert_eq!(ecs.scene.insert_resource(Frames(10)).map(|old| old.0), Some(3));
+        assert_eq!(ecs.scene.remove_resource::<Frames>().map(|old| old.0), Some(10));
+        assert!(!ecs.scene.has_resource::<Frames>());
+    }
+
+    #[test]
+    fn timesteps() {
+        let mut ecs = EcsBuilder::new(10)
+            .add_factory(Soldier::new())
+            .register_system(MoveSystem)
+            .build();
+        let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+
+        ecs.update(0.5);
+        ecs.update(0.25);
+        assert_eq!(ecs.scene.get_ref(&soldier).position.x, 0.75);
+        assert_eq!((ecs.time().delta(), ecs.time().elapsed(), ecs.time().frame()), (0.25, 0.75, 2));
+        assert_eq!(ecs.time().alpha(), 1.0);
+
+        let mut ecs = EcsBuilder::new(10)
+            .add_factory(Soldier::new())
+            .register_system(MoveSystem)
+            .fixed_timestep(4.0, 3)
+            .build();
+        let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+
+        // two steps fit in, half a step is carried over
+        ecs.update(0.625);
+        assert_eq!(ecs.time().frame(), 2);
+        assert_eq!(ecs.time().delta(), 0.25);
+        assert_eq!(ecs.time().alpha(), 0.5);
+        assert_eq!(ecs.scene.get_ref(&soldier).position.x, 0.5);
+
+        ecs.update(0.125);
+        assert_eq!(ecs.time().frame(), 3);
+        assert_eq!(ecs.time().alpha(), 0.0);
+
+        // no more than three steps per update, the rest of the time is dropped
+        ecs.update(2.0);
+        assert_eq!(ecs.time().frame(), 6);
+        assert_eq!(ecs.time().elapsed(), 1.5);
+        assert_eq!(ecs.time().alpha(), 0.0);
+        assert_eq!(ecs.scene.get_ref(&soldier).position.x, 1.5);
+
+        ecs.update(0.125);
+        assert_eq!(ecs.time().frame(), 6);
+    }
+
+    type Log = Arc<Mutex<Vec<usize>>>;
+
+    struct Logged<const ID: usize>(Log);
+
+    impl<const ID: usize> System<GameObject> for Logged<ID> {
+        fn requirements(&self, _target: &GameObject) -> bool { false }
+        fn update(&mut self, _spawn: &Spawn, _scene: &mut Scene<GameObject>) {}
+        fn update_all(&mut self, _matches: &[Spawn], _scene: &mut Scene<GameObject>) {
+            self.0.lock().unwrap().push(ID);
+        }
+    }
+
+    #[test]
+    fn stages() {
+        let log = Log::default();
+        let mut ecs = EcsBuilder::new(1)
+            .add_factory(Soldier::new())
+            .register_system_in(Logged::<1>(log.clone()), SystemOrder::new(Stage::PostUpdate))
+            .register_system_in(Logged::<2>(log.clone()), SystemOrder::new(Stage::Update).after::<Logged<3>>())
+            .register_system(Logged::<3>(log.clone()))
+            .register_system_in(Logged::<4>(log.clone()), SystemOrder::new(Stage::Startup))
+            .register_system_in(Logged::<5>(log.clone()), SystemOrder::new(Stage::PreUpdate).before::<Logged<1>>())
+            .register_system_in(Logged::<6>(log.clone()), SystemOrder::new(Stage::Cleanup).after::<Logged<7>>())
+            .build();
+
+        assert_eq!(ecs.schedule().iter().map(SystemId::index).collect::<Vec<_>>(), vec![4, 2, 1, 0, 5]);
+
+        ecs.update(1.0);
+        assert_eq!(std::mem::take(&mut *log.lock().unwrap()), vec![4, 5, 3, 2, 1, 6]);
+        ecs.update(1.0);
+        assert_eq!(std::mem::take(&mut *log.lock().unwrap()), vec![5, 3, 2, 1, 6]);
+
+        let cycle = EcsBuilder::<GameObject>::new(1)
+            .register_system_in(Logged::<1>(log.clone()), SystemOrder::new(Stage::Update).after::<Logged<2>>())
+            .register_system_in(Logged::<2>(log.clone()), SystemOrder::new(Stage::Update).after::<Logged<3>>())
+            .register_system_in(Logged::<3>(log.clone()), SystemOrder::new(Stage::Update).after::<Logged<1>>())
+            .register_system(Logged::<4>(log.clone()))
+            .try_build();
+
+        match cycle.err() {
+            Some(ScheduleError::Cycle { stage, systems }) => {
+                assert_eq!(stage, Stage::Update);
+                assert_eq!(systems.len(), 3);
+                assert!(systems[0].ends_with("Logged<1>"));
+            },
+            _ => panic!("expected a cycle"),
+        }
+
+        let conflict = EcsBuilder::<GameObject>::new(1)
+            .register_system_in(Logged::<1>(log.clone()), SystemOrder::new(Stage::PostUpdate).before::<Logged<2>>())
+            .register_system(Logged::<2>(log.clone()))
+            .try_build();
+
+        assert!(matches!(conflict.err(), Some(ScheduleError::StageConflict { .. })));
+    }
+
+    #[derive(PartialEq)]
+    enum GameState { Menu, Combat }
+
+    #[test]
+    fn run_criteria() {
+        let log = Log::default();
+        let (builder, third) = EcsBuilder::new(1)
+            .add_factory(Soldier::new())
+            .insert_resource(GameState::Menu)
+            .register_system_in(Logged::<1>(log.clone()), SystemOrder::default().run_if(RunCriteria::new().every(2)))
+            .register_system_in(Logged::<2>(log.clone()), SystemOrder::default().run_if(RunCriteria::new().in_state(GameState::Combat)))
+            .register_system_with_id(Logged::<3>(log.clone()));
+        let mut ecs = builder.build();
+
+        assert_eq!(third.index(), 2);
+        assert_eq!(ecs.system_id::<Logged<2>>().map(|id| id.index()), Some(1));
+        assert_eq!(ecs.system_id::<Logged<4>>(), None);
+
+        ecs.update(1.0);
+        ecs.update(1.0);
+        ecs.update(1.0);
+        assert_eq!(std::mem::take(&mut *log.lock().unwrap()), vec![1, 3, 3, 1, 3]);
+
+        *ecs.resource_mut::<GameState>().unwrap() = GameState::Combat;
+        ecs.set_system_enabled(third, false);
+        ecs.update(1.0);
+        ecs.update(1.0);
+        assert_eq!(std::mem::take(&mut *log.lock().unwrap()), vec![2, 1, 2]);
+        assert!(!ecs.is_system_enabled(third));
+
+        ecs.set_system_enabled(third, true);
+        ecs.set_run_criteria(ecs.system_id::<Logged<1>>().unwrap(), RunCriteria::new());
+        ecs.update(1.0);
+        assert_eq!(std::mem::take(&mut *log.lock().unwrap()), vec![1, 2, 3]);
+    }
+
+    struct Roster { log: Arc<Mutex<Vec<String>>> }
+
+    impl Roster {
+        fn note(&self, entry: &str, spawn: &Spawn) {
+            self.log.lock().unwrap().push(format!("{} {}", entry, spawn.name()));
+        }
+    }
+
+    impl System<GameObject> for Roster {
+        fn required(&self) -> Signature { GameObjectComponent::Attack.into() }
+        fn update(&mut self, _spawn: &Spawn, _scene: &mut Scene<GameObject>) {}
+
+        fn init(&mut self, scene: &mut Scene<GameObject>) {
+            let frames = scene.resource::<Frames>().unwrap().0;
+            self.log.lock().unwrap().push(format!("init {}", frames));
+        }
+        fn on_enter(&mut self, spawn: &Spawn, _scene: &mut Scene<GameObject>) { self.note("enter", spawn); }
+        fn on_exit(&mut self, spawn: &Spawn, _scene: &mut Scene<GameObject>) { self.note("exit", spawn); }
+        fn shutdown(&mut self, scene: &mut Scene<GameObject>) {
+            self.log.lock().unwrap().push(format!("shutdown {}", scene.list_spawned().len()));
+        }
+    }
+
+    #[test]
+    fn lifecycle_hooks() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut ecs = EcsBuilder::new(2)
+            .add_factory(Soldier::new())
+            .add_factory(Truck::new())
+            .insert_resource(Frames(7))
+            .register_system(Roster { log: log.clone() })
+            .build();
+        let take = || std::mem::take(&mut *log.lock().unwrap());
+        assert_eq!(take(), vec!["init 7"]);
+
+        let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+        ecs.scene.spawn("Demo truck", &1).unwrap();
+        ecs.update(1.0);
+        ecs.update(1.0);
+        assert_eq!(take(), vec!["enter Private first"]);
+
+        ecs.scene.get_mut(&soldier).attack.set_active(false);
+        ecs.scene.touch(&soldier);
+        ecs.update(1.0);
+        ecs.scene.get_mut(&soldier).attack.set_active(true);
+        ecs.scene.touch(&soldier);
+        ecs.update(1.0);
+        assert_eq!(take(), vec!["exit Private first", "enter Private first"]);
+
+        // the slot of a destroyed object is reused before the matches are refreshed
+        ecs.scene.destroy(&soldier);
+        ecs.scene.spawn("Private second", &0).unwrap();
+        ecs.update(1.0);
+        assert_eq!(take(), vec!["exit Private first", "enter Private second"]);
+
+        drop(ecs);
+        assert_eq!(take(), vec!["shutdown 2"]);
+
+        // taking out the scene shuts the systems down once
+        let mut ecs = EcsBuilder::new(2)
+            .add_factory(Soldier::new())
+            .insert_resource(Frames(0))
+            .register_system(Roster { log: log.clone() })
+            .build();
+        let soldier = ecs.scene.spawn("Private third", &0).unwrap();
+        let scene = ecs.into_scene();
+        assert_eq!(take(), vec!["init 0", "shutdown 1"]);
+        assert!(scene.exists(&soldier));
+    }
+
+    struct Count<const ID: usize>(usize);
+
+    struct Census<const ID: usize>;
+
+    impl<const ID: usize> System<GameObject> for Census<ID> {
+        fn required(&self) -> Signature { GameObjectComponent::Health.into() }
+        fn update(&mut self, _spawn: &Spawn, _scene: &mut Scene<GameObject>) {}
+    }
+
+    impl<const ID: usize> SharedSystem<GameObject> for Census<ID> {
+        fn access(&self) -> Access {
+            Access::new().read::<Health>().write_resource::<Count<ID>>()
+        }
+
+        fn update_shared(&mut self, matches: &[Spawn], scene: &Scene<GameObject>) {
+            let alive = matches.iter().filter(|spawn| scene.get_ref(spawn).health.current_hp > 0).count();
+            scene.resource_mut::<Count<ID>>().unwrap().0 += alive;
+        }
+    }
+
+    fn batch_indices(ecs: &Ecs<GameObject>) -> Vec<Vec<usize>> {
+        ecs.batches().map(|batch| batch.iter().map(SystemId::index).collect()).collect()
+    }
+
+    #[test]
+    fn parallel() {
+        let mut ecs = EcsBuilder::new(10)
+            .threads(4)
+            .add_factory(Soldier::new())
+            .insert_resource(Count::<1>(0))
+            .insert_resource(Count::<2>(0))
+            .register_shared_system(Census::<1>)
+            .register_shared_system(Census::<2>)
+            .register_system(MoveSystem)
+            .register_shared_system(Census::<1>)
+            .register_shared_system(Census::<2>)
+            .build();
+
+        // systems that aren't shared run on their own
+        assert_eq!(batch_indices(&ecs), vec![vec![0, 1], vec![2], vec![3, 4]]);
+
+        let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+        ecs.scene.spawn("Private second", &0).unwrap();
+        ecs.update(1.0);
+        ecs.update(1.0);
+
+        assert_eq!(ecs.resource::<Count<1>>().unwrap().0, 8);
+        assert_eq!(ecs.resource::<Count<2>>().unwrap().0, 8);
+        assert_eq!(ecs.scene.get_ref(&soldier).position.x, 2.0);
+        assert_eq!(ecs.last_run(ecs.schedule()[0]), ecs.last_run(ecs.schedule()[1]));
+
+        // running a batch doesn't allocate, once the buffers have grown
+        let before = allocations();
+        ecs.update(1.0);
+        assert_eq!(allocations(), before);
+
+        // writing the same resource, or being ordered against eachother, keeps systems apart
+        let ecs = EcsBuilder::new(10)
+            .add_factory(Soldier::new())
+            .register_shared_system(Census::<1>)
+            .register_shared_system(Census::<1>)
+            .register_shared_system_in(Census::<2>, SystemOrder::default().after::<Census<1>>())
+            .build();
+        assert_eq!(ecs.batches().count(), 3);
+
+        // a component write conflicts with all components when objects are stored whole
+        let build = |columnar: bool| {
+            let builder = EcsBuilder::new(10).add_factory(Soldier::new());
+            let builder = if columnar { builder.columnar() } else { builder };
+            builder.register_shared_system(ColumnMoveSystem).register_shared_system(Census::<1>).build()
+        };
+        assert_eq!(build(false).batches().count(), 2);
+        assert_eq!(build(true).batches().count(), 1);
+
+        // systems that aren't shared never leave the thread that updates the Ecs, they don't have to be Send
+        let updates = Rc::new(Cell::new(0));
+        let mut ecs = EcsBuilder::new(10)
+            .threads(2)
+            .add_factory(Soldier::new())
+            .insert_resource(Count::<1>(0))
+            .register_shared_system(Census::<1>)
+            .register_system(Local(updates.clone()))
+            .build();
+        ecs.scene.spawn("Private first", &0).unwrap();
+        ecs.update(1.0);
+        assert_eq!(updates.get(), 1);
+    }
+
+    struct Local(Rc<Cell<usize>>);
+
+    impl System<GameObject> for Local {
+        fn update(&mut self, _spawn: &Spawn, _scene: &mut Scene<GameObject>) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    struct Regenerate;
+
+    impl System<GameObject> for Regenerate {
+        fn required(&self) -> Signature { GameObjectComponent::Health.into() }
+        fn update(&mut self, _spawn: &Spawn, _scene: &mut Scene<GameObject>) {}
+    }
+
+    impl SharedSystem<GameObject> for Regenerate {
+        fn access(&self) -> Access { Access::new().write::<Health>() }
+
+        fn update_shared(&mut self, matches: &[Spawn], scene: &Scene<GameObject>) {
+            // the declared access keeps other systems from borrowing the objects in the meantime
+            scene.par_for_each(matches, |_spawn, target| target.health.heal(1)).unwrap();
+        }
+    }
+
+    #[test]
+    fn par_for_each() {
+        let mut ecs = EcsBuilder::new(10)
+            .threads(3)
+            .add_factory(Soldier::new())
+            .add_factory(Truck::new())
+            .register_shared_system(Regenerate)
+            .build();
+
+        let soldiers: Vec<Spawn> = (0..7).map(|_| ecs.scene.spawn("Private", &0).unwrap()).collect();
+        ecs.scene.spawn("Truck", &1).unwrap();
+        for soldier in &soldiers {
+            ecs.scene.get_mut(soldier).health.damage(5);
+        }
+        ecs.update(1.0);
+        ecs.update(1.0);
+
+        assert_eq!(ecs.scene.threads(), 3);
+        assert!(soldiers.iter().all(|soldier| ecs.scene.get_ref(soldier).health.current_hp == 7));
+        assert_eq!(ecs.scene.changed_since(ecs.tick() - 2).count(), 8);
+
+        // spawns can be given in any order
+        let reversed: Vec<Spawn> = soldiers.iter().rev().cloned().collect();
+        ecs.scene.par_for_each(&reversed, |spawn, target| target.position.x = *spawn.pointer() as f64).unwrap();
+        assert_eq!(ecs.scene.get_ref(&soldiers[4]).position.x, *soldiers[4].pointer() as f64);
+
+        // destroyed objects are skipped
+        ecs.scene.destroy(&soldiers[0]);
+        ecs.scene.par_for_each(&soldiers, |_spawn, target| target.position.x = -1.0).unwrap();
+        assert_eq!(ecs.scene.get_ref(&soldiers[0]).position.x, *soldiers[0].pointer() as f64);
+        assert_eq!(ecs.scene.get_ref(&soldiers[1]).position.x, -1.0);
+
+        // objects given more than once are visited once
+        let twice: Vec<Spawn> = soldiers.iter().chain(&soldiers).cloned().collect();
+        ecs.scene.par_for_each(&twice, |_spawn, target| target.position.x += 1.0).unwrap();
+        assert!(soldiers[1..].iter().all(|soldier| ecs.scene.get_ref(soldier).position.x == 0.0));
+
+        // borrowed objects are skipped, the others are still visited
+        {
+            let _borrowed = ecs.scene.get_ref(&soldiers[1]);
+            let visited = ecs.scene.par_for_each(&soldiers, |_spawn, target| target.position.x += 1.0);
+            assert_eq!(visited, Err(SceneError::AlreadyBorrowed));
+        }
+        assert_eq!(ecs.scene.get_ref(&soldiers[1]).position.x, 0.0);
+        assert!(soldiers[2..].iter().all(|soldier| ecs.scene.get_ref(soldier).position.x == 1.0));
+        ecs.scene.par_for_each(&soldiers[2..], |_spawn, target| target.position.x -= 1.0).unwrap();
+        ecs.scene.par_for_each_mut(&twice, |spawn, target| target.position.x += *spawn.pointer() as f64);
+        assert!(soldiers[1..].iter().all(|soldier| ecs.scene.get_ref(soldier).position.x == *soldier.pointer() as f64));
+        assert_eq!(ecs.scene.get_ref(&soldiers[0]).position.x, *soldiers[0].pointer() as f64);
+    }
+
+    #[test]
+    fn par_for_each_in_batch() {
+        let mut ecs = EcsBuilder::new(100)
+            .threads(4)
+            .add_factory(Soldier::new())
+            .register_shared_system(Regenerate)
+            .register_shared_system(Conscription)
+            .build();
+        assert_eq!(batch_indices(&ecs), vec![vec![0, 1]]);
+
+        let soldiers: Vec<Spawn> = (0..50).map(|_| ecs.scene.spawn("Private", &0).unwrap()).collect();
+        for soldier in &soldiers {
+            ecs.scene.get_mut(soldier).health.damage(5);
+        }
+        // the regeneration is spread over the threads the conscription leaves idle
+        for _frame in 0..3 { ecs.update(1.0); }
+
+        assert!(soldiers.iter().all(|soldier| ecs.scene.get_ref(soldier).health.current_hp == 8));
+        assert_eq!(ecs.scene.spawned().len(), 53);
+    }
+
+    struct Drafted(Spawn);
+
+    struct Conscription;
+
+    impl System<GameObject> for Conscription {
+        fn update(&mut self, _spawn: &Spawn, _scene: &mut Scene<GameObject>) {}
+    }
+
+    impl SharedSystem<GameObject> for Conscription {
+        fn access(&self) -> Access { Access::new() }
+
+        fn update_shared(&mut self, _matches: &[Spawn], scene: &Scene<GameObject>) {
+            let recruit = scene.defer_spawn("Recruit", &0).unwrap();
+            scene.send(Drafted(recruit));
+        }
+    }
+
+    #[test]
+    fn shared_commands() {
+        let mut ecs = EcsBuilder::new(1)
+            .threads(2)
+            .growth_policy(GrowthPolicy::Double)
+            .add_factory(Soldier::new())
+            .insert_resource(Count::<0>(0))
+            .register_shared_system(Conscription)
+            .register_shared_system(Census::<0>)
+            .build();
+        assert_eq!(batch_indices(&ecs), vec![vec![0, 1]]);
+
+        let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+        let mut drafted = EventReader::<Drafted>::new();
+
+        // the pool is full, so the recruit is given a slot beyond its end, which is added when the spawn is applied
+        ecs.update(1.0);
+        let recruits: Vec<Spawn> = ecs.scene.read(&mut drafted).map(|Drafted(spawn)| spawn.clone()).collect();
+        assert_eq!(recruits.len(), 1);
+        assert!(ecs.scene.exists(&recruits[0]) && ecs.scene.exists(&soldier));
+        assert_eq!(*recruits[0].pointer(), 1);
+        assert_eq!(ecs.scene.get_ref(&recruits[0]).health.current_hp, 10);
+
+        // the census runs next to the conscription, so it only counts the recruits of earlier frames
+        assert_eq!(ecs.resource::<Count<0>>().unwrap().0, 1);
+        ecs.update(1.0);
+        assert_eq!(ecs.resource::<Count<0>>().unwrap().0, 3);
+        assert_eq!(ecs.scene.read(&mut drafted).count(), 1);
+        assert_eq!(ecs.scene.spawned().len(), 3);
+    }
+
+    struct Barracks { rebuilt: Arc<AtomicUsize>, recycled: Arc<AtomicUsize> }
+
+    impl Factory<GameObject> for Barracks {
+        fn init(&mut self, _group: Group) {}
+        fn build(&self, spawn: &Spawn) -> GameObject { Soldier::new().build(spawn) }
+
+        fn rebuild_into(&self, object: &mut GameObject, spawn: &Spawn) {
+            self.rebuilt.fetch_add(1, Ordering::Relaxed);
+            *object = self.build(spawn);
+        }
+
+        fn recycle(&self, object: &mut GameObject) {
+            self.recycled.fetch_add(1, Ordering::Relaxed);
+            object.health.current_hp = 0;
+        }
+    }
+
+    #[test]
+    fn recycling() {
+        for (policy, has_health) in [(RecyclePolicy::Keep, true), (RecyclePolicy::Wipe, false)] {
+            let mut ecs = EcsBuilder::new(1)
+                .add_factory(Soldier::new())
+                .recycle_policy(policy)
+                .build();
+            let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+            ecs.scene.destroy(&soldier);
+            assert_eq!(ecs.scene.get_ref(&soldier).has_health(), has_health);
+        }
+
+        let (rebuilt, recycled) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+        let mut ecs = EcsBuilder::new(1)
+            .add_factory(Barracks { rebuilt: rebuilt.clone(), recycled: recycled.clone() })
+            .recycle_policy(RecyclePolicy::Factory)
+            .build();
+        assert_eq!(ecs.scene.recycle_policy(), &RecyclePolicy::Factory);
+
+        let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+        ecs.scene.destroy(&soldier);
+        assert_eq!(ecs.scene.get_ref(&soldier).health.current_hp, 0);
+        assert_eq!(recycled.load(Ordering::Relaxed), 1);
+
+        let soldier = ecs.scene.spawn("Private second", &0).unwrap();
+        assert_eq!(ecs.scene.get_ref(&soldier).health.current_hp, 10);
+        assert_eq!(rebuilt.load(Ordering::Relaxed), 2);
+    }
+
+    struct Deserter;
+
+    impl System<GameObject> for Deserter {
+        fn requirements(&self, _target: &GameObject) -> bool { true }
+
+        fn update(&mut self, spawn: &Spawn, scene: &mut Scene<GameObject>) {
+            assert_eq!(scene.destroy(spawn), Destroyed::Deferred);
+            assert!(scene.exists(spawn));
+        }
+    }
+
+    #[test]
+    fn destroy_during_update() {
+        let mut ecs = EcsBuilder::new(100)
+            .add_factory(Soldier::new())
+            .register_system(Deserter)
+            .build();
+
+        for _i in 0..100 { ecs.scene.spawn("Private first", &0).unwrap(); }
+
+        ecs.update(1.0);
+        assert_eq!(ecs.scene.spawned().len(), 0);
+    }
+
+    /// Counts the allocations made by the current thread, so tests running in parallel don't interfere.
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            unsafe { SystemAllocator.alloc(layout) }
+        }
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            unsafe { SystemAllocator.dealloc(ptr, layout) }
+        }
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            unsafe { SystemAllocator.realloc(ptr, layout, new_size) }
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn allocations() -> usize {
+        ALLOCATIONS.with(|count| count.get())
+    }
+
+    #[test]
+    fn speed() {
+        let soldiers = Soldier::new();
+
+        let mut ecs = EcsBuilder::new(1000)
+            .add_factory(soldiers)
+            .register_system(MoveSystem)
+            .build();
+        
+        let soldier = ecs.scene.spawn("Private first", &0).unwrap();
+
+        assert_eq!(ecs.scene.get_ref(&soldier).position.x, 0.0);
+
+        // the first update matches the soldier to the MoveSystem, without moving it
+        ecs.update(0.0);
+
+        // unoptimized builds are about a hundred times slower, they get the same second for fewer updates
+        let updates_per_second: u128 = if cfg!(debug_assertions) { 360_000 } else { 36_000_000 };
+
+        for num_tries in 0..5 {
+
+            let allocated = allocations();
+            let now = std::time::SystemTime::now();
+            for _i in 0..updates_per_second { ecs.update(1.0); }
+            let elapsed = now.elapsed().unwrap().as_millis();
+
+            assert_eq!(allocations() - allocated, 0, "updates should not allocate");
+            assert!(elapsed < 1_000, "duration should be shorter than 1 second, elapsed time was '{}' seconds", elapsed as f64 / 1_000.0);
+            assert_eq!(ecs.scene.get_ref(&soldier).position.x, (num_tries as f64 + 1.0) * updates_per_second as f64);
+        }
+    }
+
+    #[test]
+    fn update_allocations() {
+        // a crowded scene, with many systems that touch every object
+        let mut builder = EcsBuilder::new(10_000).add_factory(Soldier::new());
+        for _i in 0..15 { builder = builder.register_system(MoveSystem); }
+
+        let mut ecs = builder.build();
+        for _i in 0..10_000 { ecs.scene.spawn("Private first", &0).unwrap(); }
+
+        ecs.update(1.0);
+        for _frame in 0..3 {
+            let allocated = allocations();
+            ecs.update(1.0);
+            assert_eq!(allocations() - allocated, 0, "updates should not allocate");
+        }
+    }
+}
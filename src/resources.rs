@@ -0,0 +1,57 @@
+use std::any::{ Any, TypeId };
+use std::collections::HashMap;
+
+use crate::cell::{ AtomicRefCell, Ref, RefMut };
+use crate::scene::SceneError;
+
+
+/// Resources holds global data that is not part of any object, at most one value of each type,
+/// like the elapsed time, a random generator or the game settings. See Scene::resource.
+///
+/// Every resource can be borrowed separately, so a system can read one resource while writing to another.
+///
+#[derive(Default)]
+pub struct Resources {
+    resources: HashMap<TypeId, AtomicRefCell<Box<dyn Any + Send + Sync>>>,
+}
+
+impl Resources {
+
+    /// Add a resource, replacing and returning the resource of the same type if there was one.
+    ///
+    pub fn insert<R: Send + Sync + 'static>(&mut self, resource: R) -> Option<R> {
+        self.resources.insert(TypeId::of::<R>(), AtomicRefCell::new(Box::new(resource)))
+            .map(|old| *old.into_inner().downcast::<R>().unwrap())
+    }
+
+    pub fn remove<R: Send + Sync + 'static>(&mut self) -> Option<R> {
+        self.resources.remove(&TypeId::of::<R>())
+            .map(|old| *old.into_inner().downcast::<R>().unwrap())
+    }
+
+    pub fn contains<R: Send + Sync + 'static>(&self) -> bool {
+        self.resources.contains_key(&TypeId::of::<R>())
+    }
+
+    /// Borrow a resource, fails with ResourceNotFound if there is no resource of the type,
+    /// or with AlreadyBorrowed if the resource is currently borrowed mutably.
+    ///
+    pub fn get<R: Send + Sync + 'static>(&self) -> Result<Ref<'_, R>, SceneError> {
+        let resource = self.resources.get(&TypeId::of::<R>()).ok_or(SceneError::ResourceNotFound)?;
+        let resource = resource.try_borrow().map_err(|_| SceneError::AlreadyBorrowed)?;
+        Ok(Ref::map(resource, |resource| resource.downcast_ref::<R>().unwrap()))
+    }
+
+    /// Same as get, but borrows the resource mutably.
+    /// Fails with AlreadyBorrowed if the resource is currently borrowed in any way.
+    ///
+    pub fn get_mut<R: Send + Sync + 'static>(&self) -> Result<RefMut<'_, R>, SceneError> {
+        let resource = self.resources.get(&TypeId::of::<R>()).ok_or(SceneError::ResourceNotFound)?;
+        let resource = resource.try_borrow_mut().map_err(|_| SceneError::AlreadyBorrowed)?;
+        Ok(RefMut::map(resource, |resource| resource.downcast_mut::<R>().unwrap()))
+    }
+
+    pub fn len(&self) -> usize { self.resources.len() }
+
+    pub fn is_empty(&self) -> bool { self.resources.is_empty() }
+}
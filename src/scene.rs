@@ -1,8 +1,22 @@
 
-use std::cell::{ RefCell, Ref, RefMut };
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{ Arc, Mutex, OnceLock, PoisonError };
+use std::sync::atomic::{ AtomicBool, AtomicU64, Ordering };
 
 use crate::types::*;
 use crate::spawns::*;
+use crate::sparse::SparseSet;
+use crate::cell::{ AtomicRefCell, Ref, RefMut };
+use crate::commands::{ Command, Commands };
+use crate::spatial::{ Point, SpatialIndex };
+use crate::columns::{ Column, Columns };
+use crate::signature::Signature;
+use crate::workers::Workers;
+use crate::changes::{ Tick, Mut };
+use crate::events::{ EventChannels, EventReader, Events, ReadEvents };
+use crate::resources::Resources;
+use crate::time::Time;
 
 /// Pointer is a reference to objects in the scene, which is used to find and update these objects.
 /// A Pointer can hold a reference to an object that doesn't exist anymore,
@@ -17,6 +31,91 @@ pub enum SceneError {
     OutOfBounds, // Pointer not within boundaries as where preset during new().
     GroupNotFound, // Group not within boundaries as where preset during new().
     FactoryNotFound, // There is no factory for this Group available
+    Destroyed, // The spawned object has been destroyed.
+    StaleHandle, // The Spawn refers to an older object, its slot has been reused by a newer spawn.
+    AlreadyBorrowed, // The object is currently borrowed in a way that conflicts with the requested borrow.
+    Reserved, // The spawn has been reserved by defer_spawn, but the object is not spawned yet.
+    NoSpatialIndex, // The scene has no spatial index, see set_spatial_index.
+    ResourceNotFound, // There is no resource of the requested type, see insert_resource.
+    Columnar, // The scene stores its objects in columns, so there are no whole objects to access, see use_columns.
+    NoColumns, // The scene stores whole objects, so there are no columns to add components to, see use_columns.
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Overflow => write!(f, "spawned more objects than the pool can hold"),
+            SceneError::OutOfBounds => write!(f, "pointer is outside of the pool boundaries"),
+            SceneError::GroupNotFound => write!(f, "group does not exist"),
+            SceneError::FactoryNotFound => write!(f, "there is no factory available for this group"),
+            SceneError::Destroyed => write!(f, "spawned object has been destroyed"),
+            SceneError::StaleHandle => write!(f, "spawn refers to a slot that has been reused by a newer spawn"),
+            SceneError::AlreadyBorrowed => write!(f, "object is already borrowed"),
+            SceneError::Reserved => write!(f, "spawn is reserved but the object has not been spawned yet"),
+            SceneError::NoSpatialIndex => write!(f, "scene has no spatial index"),
+            SceneError::ResourceNotFound => write!(f, "there is no resource of this type"),
+            SceneError::Columnar => write!(f, "scene stores its objects in columns"),
+            SceneError::NoColumns => write!(f, "scene does not store its objects in columns"),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+/// GrowthPolicy tells the Scene what to do when a spawn is requested while all
+/// objects in the pool are in use.
+/// 
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum GrowthPolicy {
+    #[default]
+    Fixed, // Never grow, spawning fails with an Overflow error.
+    Double, // Double the size of the pool.
+    Chunk(usize), // Add a fixed number of objects to the pool.
+}
+
+/// RecyclePolicy tells the Scene what to do with the object of a destroyed spawn,
+/// which stays in the pool until its slot is handed out to a new spawn.
+/// 
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RecyclePolicy {
+    #[default]
+    Keep, // Leave the object as it is, until it is rebuilt by the next spawn.
+    Wipe, // Reset the object to its default, see Scene::wipe.
+    Factory, // Let the factory of its group recycle the object, see Factory::recycle.
+}
+
+/// Destroyed tells if Scene::destroy destroyed an object right away,
+/// deferred the destroy because a system is being updated, or had nothing to destroy.
+/// 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Destroyed {
+    Now, // The object is gone, exists returns false from here on.
+    Deferred, // The object keeps existing until the next sync point, see SyncPoint.
+    Missing, // The spawn is stale or its object was already destroyed, nothing happened.
+}
+
+/// Broadphase tells for_each_pair which pairs of objects to visit,
+/// pruning pairs that can't interact before they are handed to the callback.
+/// 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Broadphase<'a> {
+    All, // Every pair of spawned objects.
+    Groups(&'a [(Group, Group)]), // Only pairs of an object in the first group with an object in the second group.
+    Within(f64), // Only pairs within this distance of eachother, as found by the spatial index.
+}
+
+/// Spatial combines a spatial index with the function that tells where an object is positioned.
+/// 
+pub(crate) struct Spatial<T> {
+    pub(crate) locate: fn(&T) -> Point,
+    pub(crate) index: Box<dyn SpatialIndex>,
+}
+
+/// ColumnStorage holds the objects of a Scene split up in component columns.
+/// 
+struct ColumnStorage<T> {
+    scatter: fn(T, Pointer, &mut Columns),
+    columns: Columns,
 }
 
 /// Scene is basically a manager for all entities and where to find them.
@@ -27,20 +126,39 @@ pub enum SceneError {
 /// 
 pub struct Scene<T: Entity> {
     factories: Vec<Box::<dyn Factory<T>>>,
-    pool: Vec<RefCell<T>>,
+    pool: Vec<AtomicRefCell<T>>,
+    changed: Vec<AtomicU64>,
     spawns: Vec<Spawn>,
-    free: Vec<Pointer>,
-    in_use: Vec<Spawn>,
-    groups: Vec<Vec<Pointer>>,
-    itter_count: usize,
+    in_use: SparseSet<Spawn>,
+    groups: Vec<SparseSet<()>>,
+    touched: Mutex<SparseSet<bool>>, // changed objects, and whether the Ecs has to match them to its systems again
+    visiting: Mutex<Vec<Pointer>>, // reused by par_for_each to sort the pointers it visits
+    names: HashMap<Arc<str>, Vec<Vec<Pointer>>>, // spawned pointers by name, per group
+    name_slots: SparseSet<usize>, // position of a pointer in its list of names
+    spatial: Option<Spatial<T>>,
+    columns: Option<ColumnStorage<T>>,
+    commands: Mutex<Commands<T>>, // also holds the free slots and the events sent since the last apply_events
+    events: EventChannels,
+    resources: Resources,
+    time: Time,
+    iterating: bool,
+    tick: Tick,
+    threads: usize,
+    workers: OnceLock<Workers>, // started the first time work is spread over more than one thread
+    min_size: usize,
+    max_size: Option<usize>,
+    growth: GrowthPolicy,
+    recycle: RecyclePolicy,
+    retired_generation: Generation,
 }
 
 impl<T: Entity> Scene<T>  {
 
     /// Create a new Scene entity manager instance.
     /// 
-    /// By setting the 'size' parameter, you preset the maximum amount of 
+    /// By setting the 'size' parameter, you preset the amount of 
     /// Object the new pool can hold and therefore spawn.
+    /// The pool can only hold more objects if a growth policy is set.
     /// 
     /// Factories are custom object factories that implement the Factory trait
     /// which can be called upon, by the Scene, when spawning a new object. 
@@ -48,31 +166,175 @@ impl<T: Entity> Scene<T>  {
     /// objects by customizing the output of these factories.
     /// 
     pub fn new(size: usize, factories: Vec<Box::<dyn Factory<T>>>) -> Self {
+        Scene::build(size, factories, None, default_threads())
+    }
 
-        let mut pool: Vec<RefCell<T>> = Vec::new();
-        pool.resize_with(size, || { RefCell::new(T::default()) });
+    /// Same as new, but the scene stores its objects in columns from the start, see use_columns.
+    /// 
+    pub fn new_columnar(size: usize, factories: Vec<Box::<dyn Factory<T>>>, scatter: fn(T, Pointer, &mut Columns)) -> Self {
+        Scene::build(size, factories, Some(scatter), default_threads())
+    }
+
+    /// Creates the scene, its worker threads are only started once they are needed, see set_threads.
+    /// 
+    pub(crate) fn build(size: usize, factories: Vec<Box::<dyn Factory<T>>>, scatter: Option<fn(T, Pointer, &mut Columns)>, threads: usize) -> Self {
+        let columns = scatter.map(|scatter| ColumnStorage { scatter, columns: Columns::default() });
 
-        let mut spawns: Vec<Spawn> = Vec::new();
-        spawns.resize_with(size, Spawn::default);
-        
-        let mut free: Vec<Pointer> = Vec::with_capacity(size);
-        let in_use: Vec<Spawn> = Vec::with_capacity(size);
+        // columnar scenes don't keep whole objects, so they have no pool to fill
+        let pool: Vec<AtomicRefCell<T>> = match columns {
+            Some(_) => Vec::new(),
+            None => Vec::with_capacity(size),
+        };
+        let spawns: Vec<Spawn> = Vec::with_capacity(size);
+        let in_use: SparseSet<Spawn> = SparseSet::with_capacity(size);
 
-        let mut groups: Vec<Vec<Pointer>> = Vec::new();
-        groups.resize_with(factories.len(), Vec::new);
+        let mut groups: Vec<SparseSet<()>> = Vec::new();
+        groups.resize_with(factories.len(), SparseSet::default);
 
-        for i in 0..size { 
-            spawns[i].pointer = i; 
-            free.push(i);
+        let mut scene = Scene { 
+            factories, pool, spawns, in_use, groups, 
+            changed: Vec::with_capacity(size),
+            touched: Mutex::default(),
+            visiting: Mutex::default(),
+            names: HashMap::new(),
+            name_slots: SparseSet::default(),
+            spatial: None,
+            columns,
+            commands: Mutex::default(),
+            events: EventChannels::default(),
+            resources: Resources::default(),
+            time: Time::default(),
+            iterating: false,
+            tick: 1,
+            threads,
+            workers: OnceLock::new(),
+            min_size: size,
+            max_size: None,
+            growth: GrowthPolicy::Fixed,
+            recycle: RecyclePolicy::Keep,
+            retired_generation: 0,
+        };
+        scene.resize_pool(size);
+        scene
+    }
+
+    /// Set how the pool should grow when spawning while all objects are in use.
+    /// The pool will never grow beyond 'max_size' objects, if one is given.
+    /// 
+    pub fn set_growth_policy(&mut self, growth: GrowthPolicy, max_size: Option<usize>) {
+        self.growth = growth;
+        self.max_size = max_size;
+    }
+
+    pub fn growth_policy(&self) -> &GrowthPolicy {
+        &self.growth
+    }
+
+    /// Set what happens to the objects of destroyed spawns, by default they are kept as they are.
+    /// 
+    pub fn set_recycle_policy(&mut self, recycle: RecyclePolicy) {
+        self.recycle = recycle;
+    }
+
+    pub fn recycle_policy(&self) -> &RecyclePolicy {
+        &self.recycle
+    }
+
+    /// Grow the pool as described by the growth policy.
+    /// Returns false if the pool could not grow any further.
+    /// 
+    fn grow(&mut self) -> bool {
+        let size = self.spawns.len();
+        let new_size = self.grown_size(size);
+        if new_size <= size { 
+            return false; 
         }
+        self.resize_pool(new_size);
+        true
+    }
 
-        for group in &mut groups {
-            group.resize_with(size, Pointer::default);
+    /// The size a pool of 'size' objects grows to, as described by the growth policy.
+    /// 
+    fn grown_size(&self, size: usize) -> usize {
+        let new_size = match self.growth {
+            GrowthPolicy::Fixed => size,
+            GrowthPolicy::Double => (size * 2).max(1),
+            GrowthPolicy::Chunk(chunk) => size + chunk,
+        };
+        match self.max_size {
+            Some(max_size) => new_size.min(max_size),
+            None => new_size,
         }
+    }
 
-        Scene { factories, pool, spawns, free, in_use, groups, itter_count: 0, } 
+    /// Adds the slots reserved by defer_spawn beyond the end of the pool, 
+    /// which could not be added while the scene was shared.
+    /// 
+    fn cover_reserved(&mut self) {
+        let slots = self.commands.get_mut().unwrap().slots;
+        if slots > self.spawns.len() {
+            self.resize_pool(slots);
+        }
     }
 
+    /// Shrink the pool back to its initial size, or to the highest object still in use (or reserved) if that is larger.
+    /// Use this after a spike in spawns made the pool grow, to release the memory of unused objects.
+    /// Existing spawns stay valid, spawns of objects in released slots will be reported as stale.
+    /// 
+    pub fn shrink_to_fit(&mut self) {
+        let commands = self.commands.get_mut().unwrap();
+        let in_use = self.in_use.pointers().iter()
+            .chain(commands.reserved.pointers())
+            .max().map_or(0, |pointer| pointer + 1);
+        self.resize_pool(self.min_size.max(in_use));
+
+        let commands = self.commands.get_mut().unwrap();
+        commands.free.shrink_to_fit();
+        commands.reserved.shrink_to_fit();
+        self.pool.shrink_to_fit();
+        self.changed.shrink_to_fit();
+        self.spawns.shrink_to_fit();
+        self.in_use.shrink_to_fit();
+        self.touched.get_mut().unwrap().shrink_to_fit();
+        self.name_slots.shrink_to_fit();
+        for group in &mut self.groups { group.shrink_to_fit(); }
+    }
+
+    fn resize_pool(&mut self, new_size: usize) {
+        let size = self.spawns.len();
+        let commands = self.commands.get_mut().unwrap();
+
+        if new_size < size {
+            for spawn in &self.spawns[new_size..] {
+                self.retired_generation = self.retired_generation.max(spawn.generation);
+            }
+            self.pool.truncate(new_size);
+            self.changed.truncate(new_size);
+            self.spawns.truncate(new_size);
+            commands.free.retain(|pointer| *pointer < new_size);
+            commands.slots = new_size;
+        } 
+        else {
+            if self.columns.is_none() {
+                self.pool.resize_with(new_size, || { AtomicRefCell::new(T::default()) });
+            }
+            self.changed.resize_with(new_size, AtomicU64::default);
+            self.spawns.resize_with(new_size, Spawn::default);
+
+            for pointer in size..new_size {
+                self.spawns[pointer].pointer = pointer;
+                self.spawns[pointer].generation = self.retired_generation;
+            }
+            // free is used as a stack, lower pointers are pushed last to be handed out first.
+            // Slots defer_spawn added beyond the end of the pool are free or reserved already.
+            for pointer in (commands.slots.max(size)..new_size).rev() {
+                commands.free.push(pointer);
+            }
+            commands.slots = commands.slots.max(new_size);
+        }
+    }
+
+    #[allow(clippy::borrowed_box)]
     pub fn get_factory(&self, group: &Group) -> &Box::<dyn Factory<T>> {
         &self.factories[*group]
     }
@@ -84,30 +346,138 @@ impl<T: Entity> Scene<T>  {
     /// Returns a cloned list of spawn currently in use.
     /// 
     pub fn list_spawned(&self) -> Vec<Spawn> {
-        self.in_use.clone()
+        self.in_use.values().to_vec()
     }
 
-    /// Returns a reference to a RefCell box containing the requested object.
+    /// Same as list_spawned, but borrows the list instead of cloning it.
+    /// 
+    pub fn spawned(&self) -> &[Spawn] {
+        self.in_use.values()
+    }
+
+    /// While the scene is itterating, objects that are destroyed stay in place (and keep existing) 
+    /// until the next sync point, so an index into spawned() stays valid during the itteration.
+    /// Spawning is allowed, new objects are added at the end of spawned().
+    /// 
+    pub(crate) fn set_iterating(&mut self, iterating: bool) {
+        self.iterating = iterating;
+    }
+
+    /// Returns a borrowed reference to the requested object.
     /// If the spawned object has been destroyed the inactive object will still be returned.
     /// You can use the methodes exists and exists_in_group to find out if objects are currently active.
     /// 
-    pub fn get_ref(&self, spawn: &Spawn) -> Ref<T> { 
+    /// Panics when the spawn is stale, meaning its slot has been handed out to a newer spawn,
+    /// or when the scene stores its objects in columns, see use_columns.
+    /// 
+    pub fn get_ref(&self, spawn: &Spawn) -> Ref<'_, T> { 
+        self.assert_current(spawn);
+        self.assert_pooled();
         self.pool[spawn.pointer].borrow()
     }
 
     /// Same as the get_ref methode but returns a mutable reference.
+    /// The object is stamped as changed when it is accessed mutably, see changed_since.
+    /// 
+    pub fn get_mut(&self, spawn: &Spawn) -> Mut<'_, T> { 
+        self.assert_current(spawn);
+        self.assert_pooled();
+        self.stamp_on_drop(spawn.pointer, self.pool[spawn.pointer].borrow_mut())
+    }
+
+    /// Same as get_ref, but instead of panicking or returning a destroyed object, 
+    /// an error is returned when the spawn is out of bounds, stale or destroyed,
+    /// when the object is currently borrowed mutably, or when the scene stores its objects in columns.
+    /// 
+    pub fn try_get_ref(&self, spawn: &Spawn) -> Result<Ref<'_, T>, SceneError> { 
+        self.check_pooled()?;
+        self.check_spawned(spawn)?;
+        self.pool[spawn.pointer].try_borrow().map_err(|_| SceneError::AlreadyBorrowed)
+    }
+
+    /// Same as try_get_ref, but returns a mutable reference.
+    /// Fails with AlreadyBorrowed when the object is borrowed in any way,
+    /// which makes it safe to use when touching two objects at once.
+    /// 
+    pub fn try_get_mut(&self, spawn: &Spawn) -> Result<Mut<'_, T>, SceneError> { 
+        self.check_pooled()?;
+        self.check_spawned(spawn)?;
+        let object = self.pool[spawn.pointer].try_borrow_mut().map_err(|_| SceneError::AlreadyBorrowed)?;
+        Ok(self.stamp_on_drop(spawn.pointer, object))
+    }
+
+    fn stamp_on_drop<'a>(&'a self, pointer: Pointer, object: RefMut<'a, T>) -> Mut<'a, T> {
+        // only the spatial index needs to know which objects moved
+        let touched = self.spatial.as_ref().map(|_| &self.touched);
+        Mut::new(object, pointer, &self.changed[pointer], touched, self.tick)
+    }
+
+    fn check_spawned(&self, spawn: &Spawn) -> Result<(), SceneError> {
+        if self.exists(spawn) {
+            Ok(())
+        } else if self.is_reserved(spawn) { 
+            Err(SceneError::Reserved) 
+        } else if spawn.pointer >= self.spawns.len() { 
+            Err(SceneError::OutOfBounds) 
+        } else if !self.is_current(spawn) { 
+            Err(SceneError::StaleHandle) 
+        } else { 
+            Err(SceneError::Destroyed) 
+        }
+    }
+
+    /// Checks if the spawn has been handed out by defer_spawn, and its object is not spawned yet.
+    /// 
+    fn is_reserved(&self, spawn: &Spawn) -> bool {
+        self.commands.lock().unwrap().reserved.get(spawn.pointer) == Some(spawn)
+    }
+
+    /// Checks if the spawn still refers to the latest object spawned into its slot.
+    /// A spawn that has been destroyed is still current until its slot is reused by a new spawn.
     /// 
-    pub fn get_mut(&self, spawn: &Spawn) -> RefMut<T> { 
-        self.pool[spawn.pointer].borrow_mut()
+    pub fn is_current(&self, spawn: &Spawn) -> bool {
+        spawn.pointer < self.spawns.len()
+        && self.spawns[spawn.pointer].generation == spawn.generation
+    }
+
+    fn assert_current(&self, spawn: &Spawn) {
+        assert!(
+            self.is_current(spawn), 
+            "stale spawn '{}': slot {} has been reused by a newer spawn", 
+            spawn.name(), spawn.pointer
+        );
+    }
+
+    fn check_pooled(&self) -> Result<(), SceneError> {
+        match self.columns {
+            Some(_) => Err(SceneError::Columnar),
+            None => Ok(()),
+        }
+    }
+
+    fn assert_pooled(&self) {
+        assert!(self.columns.is_none(), "the scene stores its objects in columns, use component or column instead");
+    }
+
+    /// The objects that can be borrowed whole, which are none when the scene stores its objects in columns.
+    /// 
+    fn pooled<'a>(&self, pointers: &'a [Pointer]) -> &'a [Pointer] {
+        match self.columns {
+            Some(_) => &[],
+            None => pointers,
+        }
     }
 
     /// Run a custom test that tells if all active (spawned) objects comply to the predicate specified.
     /// 
+    /// NOTE: The searches, queries and comparisons over whole objects don't find any objects 
+    /// when the scene stores its objects in columns, see use_columns.
+    /// 
     pub fn test_all<P> (&self, predicate: &mut P) -> bool
         where P: FnMut(&T) -> bool
     {
-        for spawn in &self.in_use {
-            if !predicate(&self.pool[spawn.pointer].borrow()) {
+        for pointer in self.pooled(self.in_use.pointers()) {
+            if !predicate(&self.pool[*pointer].borrow()) {
                 return false;
             }
         }
@@ -115,31 +485,67 @@ impl<T: Entity> Scene<T>  {
     }
 
     /// Find an active (spawned) object by its spawn name.
+    /// Spawns are indexed by name, so this does not need to itterate over all objects.
     /// 
     pub fn find_spawn(&self, name: &str) -> Option<Spawn> {
-
-        for spawn in &self.in_use { 
-            if self.spawns[spawn.pointer].name() == name { 
-                return Some(self.spawns[spawn.pointer].clone()); 
-            }
-        }
-        None
+        self.named(name).next().map(|pointer| self.spawns[*pointer].clone())
     }
 
     /// Find an active (spawned) object by its spawn name and factory group.
-    /// This methode can be faster as find_spawn, when there are multiple groups, sinds it does not need to itterate over all objects.
-    /// Find_in_group can also come in handy when using the same name in different groups (sinds spawn names do not need to be unique).
+    /// Find_in_group can come in handy when using the same name in different groups (sinds spawn names do not need to be unique).
     /// 
     pub fn find_spawn_in_group(&self, name: &str, group: Group) -> Option<Spawn> {
+        self.names.get(name)
+            .and_then(|groups| groups.get(group))
+            .and_then(|pointers| pointers.first())
+            .map(|pointer| self.spawns[*pointer].clone())
+    }
 
-        if group >= self.groups.len() { return None; }
+    /// Returns the number of active (spawned) objects with the same name.
+    /// 
+    pub fn count_named(&self, name: &str) -> usize {
+        self.names.get(name).map_or(0, |groups| groups.iter().map(Vec::len).sum())
+    }
 
-        for pointer in &self.groups[group] { 
-            if self.spawns[*pointer].name() == name {
-                return Some(self.spawns[*pointer].clone());
+    fn named(&self, name: &str) -> impl Iterator<Item = &Pointer> + '_ {
+        self.names.get(name).into_iter().flatten().flatten()
+    }
+
+    /// Returns the shared string of a name that is in use, or a new one.
+    /// 
+    fn intern_name(&self, name: &str) -> Name {
+        match self.names.get_key_value(name) {
+            Some((shared, _)) => Name::from_shared(shared.clone()),
+            None => Name::from(name),
+        }
+    }
+
+    fn index_name(&mut self, pointer: Pointer) {
+        let group = self.spawns[pointer].group;
+        let groups = self.names.entry(self.spawns[pointer].name.shared()).or_default();
+        if groups.len() <= group {
+            groups.resize_with(group + 1, Vec::new);
+        }
+        self.name_slots.insert(pointer, groups[group].len());
+        groups[group].push(pointer);
+    }
+
+    /// Removes the pointer from its list of names, by moving the last pointer of the list into its place.
+    /// 
+    fn unindex_name(&mut self, pointer: Pointer) {
+        let Some(slot) = self.name_slots.remove(pointer) else { return };
+        let spawn = &self.spawns[pointer];
+
+        if let Some(groups) = self.names.get_mut(spawn.name()) {
+            let pointers = &mut groups[spawn.group];
+            pointers.swap_remove(slot);
+            if let Some(moved) = pointers.get(slot) {
+                self.name_slots.insert(*moved, slot);
+            }
+            if groups.iter().all(Vec::is_empty) { 
+                self.names.remove(spawn.name()); 
             }
         }
-        None
     }
 
     /// As find_spawn, but lets you write a custom predicate using object values.
@@ -147,9 +553,9 @@ impl<T: Entity> Scene<T>  {
     pub fn search_components<P> (&self, mut predicate: P) -> Option<Spawn>
         where P: FnMut(&T) -> bool {
 
-        for spawn in &self.in_use { 
-            if predicate(&self.pool[spawn.pointer].borrow()) {
-                return Some(self.spawns[spawn.pointer].clone());
+        for pointer in self.pooled(self.in_use.pointers()) { 
+            if predicate(&self.pool[*pointer].borrow()) {
+                return Some(self.spawns[*pointer].clone());
             }
         }
         None
@@ -162,7 +568,7 @@ impl<T: Entity> Scene<T>  {
 
         if group >= self.groups.len() { return None; }
 
-        for pointer in &self.groups[group] { 
+        for pointer in self.pooled(self.groups[group].pointers()) { 
             if predicate(&self.pool[*pointer].borrow()) {
                 return Some(self.spawns[*pointer].clone());
             }
@@ -170,18 +576,94 @@ impl<T: Entity> Scene<T>  {
         None
     }
 
+    /// As search_components, but itterates over all objects for which the predicate succeeds, 
+    /// together with a borrow of the object.
+    /// Objects that are currently borrowed mutably, like the object being updated by a system, are skipped.
+    /// 
+    pub fn query<'a, P> (&'a self, predicate: P) -> impl Iterator<Item = (Spawn, Ref<'a, T>)> + 'a
+        where P: FnMut(&T) -> bool + 'a 
+    {
+        self.query_pointers(self.in_use.pointers(), predicate)
+    }
+
+    /// Same as query, but borrows the objects mutably. Objects that are currently borrowed in any way are skipped.
+    /// 
+    pub fn query_mut<'a, P> (&'a self, predicate: P) -> impl Iterator<Item = (Spawn, Mut<'a, T>)> + 'a
+        where P: FnMut(&T) -> bool + 'a 
+    {
+        self.query_pointers_mut(self.in_use.pointers(), predicate)
+    }
+
+    /// As query, but only itterates over the objects of one group.
+    /// 
+    pub fn query_in_group<'a, P> (&'a self, group: Group, predicate: P) -> impl Iterator<Item = (Spawn, Ref<'a, T>)> + 'a
+        where P: FnMut(&T) -> bool + 'a 
+    {
+        self.query_pointers(self.group_pointers(group), predicate)
+    }
+
+    /// As query_mut, but only itterates over the objects of one group.
+    /// 
+    pub fn query_in_group_mut<'a, P> (&'a self, group: Group, predicate: P) -> impl Iterator<Item = (Spawn, Mut<'a, T>)> + 'a
+        where P: FnMut(&T) -> bool + 'a 
+    {
+        self.query_pointers_mut(self.group_pointers(group), predicate)
+    }
+
+    /// Same as query, but returns the results ordered by a key, like the distance to an object.
+    /// 
+    pub fn query_sorted_by_key<'a, P, K, F> (&'a self, predicate: P, mut key: F) -> Vec<(Spawn, Ref<'a, T>)>
+        where P: FnMut(&T) -> bool + 'a, K: PartialOrd, F: FnMut(&T) -> K
+    {
+        let mut results: Vec<(Spawn, Ref<'a, T>)> = self.query(predicate).collect();
+        results.sort_by(|(_, a), (_, b)| {
+            key(a).partial_cmp(&key(b)).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        results
+    }
+
+    /// As find_spawn, but itterates over all active (spawned) objects with the name.
+    /// 
+    pub fn find_all<'a>(&'a self, name: &str) -> impl Iterator<Item = Spawn> + 'a {
+        self.named(name).map(move |pointer| self.spawns[*pointer].clone())
+    }
+
+    fn query_pointers<'a, P> (&'a self, pointers: &'a [Pointer], mut predicate: P) -> impl Iterator<Item = (Spawn, Ref<'a, T>)> + 'a
+        where P: FnMut(&T) -> bool + 'a 
+    {
+        self.pooled(pointers).iter().filter_map(move |pointer| {
+            let object = self.pool[*pointer].try_borrow().ok()?;
+            match predicate(&object) {
+                true => Some((self.spawns[*pointer].clone(), object)),
+                false => None,
+            }
+        })
+    }
+
+    fn query_pointers_mut<'a, P> (&'a self, pointers: &'a [Pointer], mut predicate: P) -> impl Iterator<Item = (Spawn, Mut<'a, T>)> + 'a
+        where P: FnMut(&T) -> bool + 'a 
+    {
+        self.pooled(pointers).iter().filter_map(move |pointer| {
+            let object = self.pool[*pointer].try_borrow_mut().ok()?;
+            match predicate(&object) {
+                true => Some((self.spawns[*pointer].clone(), self.stamp_on_drop(*pointer, object))),
+                false => None,
+            }
+        })
+    }
+
     /// Compare an objects values, with the values of all other objects. 
     /// Returns an Option of the Spawn on which the predicate succeeded first, or None is all comparisons failed.
     /// 
     pub fn compare_against<F> (&self, against: Spawn, mut on_compare: F) -> Option<Spawn>
         where F: FnMut(&T, &T) -> bool
     {
-        for spawn in &self.in_use {
+        for pointer in self.pooled(self.in_use.pointers()) {
             if on_compare(
                 &self.pool[against.pointer].borrow(), 
-                &self.pool[spawn.pointer].borrow()
+                &self.pool[*pointer].borrow()
             ){
-                return Some(spawn.clone());
+                return Some(self.spawns[*pointer].clone());
             }
         }
         None
@@ -193,73 +675,789 @@ impl<T: Entity> Scene<T>  {
     pub fn compare_all<F> (&self, mut on_compare: F) -> Option<(Spawn, Spawn)>
         where F: FnMut(&T, &T) -> bool
     {
-        for spawn_a in &self.in_use {
-            for spawn_b in &self.in_use {
-                if spawn_a == spawn_b { continue; }
+        let pointers = self.pooled(self.in_use.pointers());
+        for a in pointers {
+            for b in pointers {
+                if a == b { continue; }
 
                 else if on_compare(
-                    &self.pool[spawn_a.pointer].borrow(), 
-                    &self.pool[spawn_b.pointer].borrow()
+                    &self.pool[*a].borrow(), 
+                    &self.pool[*b].borrow()
                 ){
-                    return Some( (spawn_a.clone(), spawn_b.clone()) );
+                    return Some( (self.spawns[*a].clone(), self.spawns[*b].clone()) );
                 }
             }
         }
         None
     }
     
+    /// Itterates over every unordered pair of spawned objects once, 
+    /// so when (a, b) is visited (b, a) is not.
+    /// 
+    pub fn pairs(&self) -> impl Iterator<Item = (&Spawn, &Spawn)> + '_ {
+        let spawns = self.in_use.values();
+        (0..spawns.len()).flat_map(move |a| {
+            spawns[a + 1..].iter().map(move |b| (&spawns[a], b))
+        })
+    }
+
+    /// Calls 'on_pair' for every unordered pair of spawned objects that passes the broadphase,
+    /// with mutable access to both objects. Each pair is visited once.
+    /// 
+    /// Broadphase::Groups visits pairs with the first object from the first group and the second object from the second group,
+    /// if the same two groups are listed twice, their pairs are visited twice.
+    /// Broadphase::Within uses the positions as stored in the spatial index, and fails if the scene has none.
+    /// Fails with Columnar when the scene stores its objects in columns.
+    /// 
+    /// NOTE: Both objects of every visited pair are stamped as changed, see changed_since.
+    /// 
+    pub fn for_each_pair<F> (&mut self, broadphase: Broadphase, mut on_pair: F) -> Result<(), SceneError>
+        where F: FnMut((&Spawn, &mut T), (&Spawn, &mut T))
+    {
+        self.check_pooled()?;
+        let pool = &mut self.pool;
+        let spawns = &self.spawns;
+        let changed = &self.changed;
+        let touched = self.touched.get_mut().unwrap();
+        let tick = self.tick;
+        let mut visit = |a: Pointer, b: Pointer| {
+            let (object_a, object_b) = pair_mut(pool, a, b);
+            on_pair((&spawns[a], object_a), (&spawns[b], object_b));
+            for pointer in [a, b] {
+                changed[pointer].store(tick, Ordering::Relaxed);
+                if !touched.contains(pointer) { touched.insert(pointer, false); }
+            }
+        };
+
+        match broadphase {
+            Broadphase::All => {
+                let pointers = self.in_use.pointers();
+                for a in 0..pointers.len() {
+                    for b in &pointers[a + 1..] { visit(pointers[a], *b); }
+                }
+            },
+            Broadphase::Groups(groups) => {
+                for (group_a, group_b) in groups {
+                    let (pointers_a, pointers_b) = match (self.groups.get(*group_a), self.groups.get(*group_b)) {
+                        (Some(a), Some(b)) => (a.pointers(), b.pointers()),
+                        _ => continue,
+                    };
+                    for a in 0..pointers_a.len() {
+                        // pairs within the same group are only visited once
+                        let others = if group_a == group_b { &pointers_b[a + 1..] } else { pointers_b };
+                        for b in others { visit(pointers_a[a], *b); }
+                    }
+                }
+            },
+            Broadphase::Within(distance) => {
+                let spatial = self.spatial.as_ref().ok_or(SceneError::NoSpatialIndex)?;
+                let mut nearby = Vec::new();
+
+                for a in self.in_use.pointers() {
+                    let center = match spatial.index.position(*a) {
+                        Some(center) => center,
+                        None => continue,
+                    };
+                    nearby.clear();
+                    spatial.index.within_radius(center, distance, &mut |b, _| {
+                        if b > *a { nearby.push(b); }
+                    });
+                    for b in &nearby { visit(*a, *b); }
+                }
+            },
+        }
+        Ok(())
+    }
+
+    /// Calls 'f' for the objects of all given spawns, spread over the threads of the scene, see set_threads.
+    /// The objects are split up into chunks, one for every thread, and every object is borrowed mutably while 'f' runs.
+    /// Only needs shared access to the scene, so shared systems can use it as well, see SharedSystem.
+    /// When called from a system that runs in parallel with others, the threads that are idle help out.
+    /// 
+    /// Every object is visited once, in the order of their Pointers, also when its spawn is given more than once.
+    /// Spawns of objects that don't exist, because they are stale or destroyed, are skipped, see exists.
+    /// Use par_for_each_mut when the scene isn't shared, it doesn't have to check the borrows.
+    /// 
+    /// Returns AlreadyBorrowed when objects are borrowed by someone else, those objects are skipped
+    /// while all others are still visited. Returns Columnar, without visiting anything, 
+    /// when the scene stores its objects in columns.
+    /// 
+    /// NOTE: Every visited object is stamped as changed, see changed_since.
+    /// 
+    pub fn par_for_each<F> (&self, spawns: &[Spawn], f: F) -> Result<(), SceneError>
+        where F: Fn(&Spawn, &mut T) + Sync
+    {
+        self.check_pooled()?;
+        // the buffer is taken out, so par_for_each can be called by several systems at the same time
+        let mut pointers = std::mem::take(&mut *self.visiting.lock().unwrap());
+        self.sort_existing(spawns, &mut pointers);
+        let chunk_size = pointers.len().div_ceil(self.threads).max(1);
+        let borrowed = AtomicBool::new(false);
+
+        self.for_each_parallel(pointers.len().div_ceil(chunk_size), self.threads, &|chunk| {
+            for pointer in pointers[chunk * chunk_size..].iter().take(chunk_size) {
+                let Ok(mut object) = self.pool[*pointer].try_borrow_mut() else {
+                    borrowed.store(true, Ordering::Relaxed);
+                    continue;
+                };
+                f(self.in_use.get(*pointer).unwrap(), &mut object);
+                self.changed[*pointer].store(self.tick, Ordering::Relaxed);
+            }
+        });
+
+        let mut touched = self.touched.lock().unwrap_or_else(PoisonError::into_inner);
+        for pointer in &pointers {
+            if !touched.contains(*pointer) { touched.insert(*pointer, false); }
+        }
+        drop(touched);
+        *self.visiting.lock().unwrap() = pointers;
+
+        match borrowed.into_inner() {
+            true => Err(SceneError::AlreadyBorrowed),
+            false => Ok(()),
+        }
+    }
+
+    /// Same as par_for_each, but the pool is split up into separate slices for every chunk of objects,
+    /// so the objects are handed out without checking if they are borrowed.
+    /// 
+    pub fn par_for_each_mut<F> (&mut self, spawns: &[Spawn], f: F)
+        where F: Fn(&Spawn, &mut T) + Sync
+    {
+        self.assert_pooled();
+        let mut pointers = std::mem::take(self.visiting.get_mut().unwrap());
+        self.sort_existing(spawns, &mut pointers);
+        let chunk_size = pointers.len().div_ceil(self.threads).max(1);
+        let (in_use, changed, tick) = (&self.in_use, &self.changed, self.tick);
+
+        // every call below takes the next chunk of objects, the iterator doesn't allocate
+        let chunks = Mutex::new(PoolChunks { pool: &mut self.pool[..], offset: 0, chunks: pointers.chunks(chunk_size) });
+        spread(&self.workers, self.threads, pointers.len().div_ceil(chunk_size), self.threads, &|_| {
+            let Some((chunk, offset, objects)) = chunks.lock().unwrap_or_else(PoisonError::into_inner).next() else { return };
+            for pointer in chunk {
+                f(in_use.get(*pointer).unwrap(), objects[pointer - offset].get_mut());
+                changed[*pointer].store(tick, Ordering::Relaxed);
+            }
+        });
+
+        let touched = self.touched.get_mut().unwrap();
+        for pointer in &pointers {
+            if !touched.contains(*pointer) { touched.insert(*pointer, false); }
+        }
+        *self.visiting.get_mut().unwrap() = pointers;
+    }
+
+    /// Fills 'pointers' with the Pointers of the spawns that exist, sorted and without duplicates.
+    /// 
+    fn sort_existing(&self, spawns: &[Spawn], pointers: &mut Vec<Pointer>) {
+        pointers.clear();
+        pointers.extend(spawns.iter().filter(|spawn| self.exists(spawn)).map(|spawn| spawn.pointer));
+        pointers.sort_unstable();
+        pointers.dedup();
+    }
+
+    /// Set the number of threads the scene can spread work over, see par_for_each.
+    /// By default as many as the machine can run in parallel.
+    /// The first time work is spread over more than one thread, the scene starts 'threads - 1' worker threads,
+    /// which keep running until it is dropped, the calling thread makes up the last one.
+    /// 
+    pub fn set_threads(&mut self, threads: usize) {
+        assert!(threads > 0, "the scene needs at least one thread to run on");
+        if threads != self.threads {
+            self.threads = threads;
+            self.workers = OnceLock::new();
+        }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Calls 'f' once for every index from 0 to 'count', spread over at most 'threads' threads, see Workers::for_each.
+    /// The worker threads are only started when there is work for more than one thread.
+    /// 
+    pub(crate) fn for_each_parallel(&self, count: usize, threads: usize, f: &(dyn Fn(usize) + Sync)) {
+        spread(&self.workers, self.threads, count, threads, f);
+    }
+
     /// Spawn a new object. Spawned objects are updated every frame by the core ECS system.
     /// The spawn methode activates a new object that will inherit all the settings of the factory of the corresponding group. 
     /// A name must be added to the spawn, this can be used to find the spawn if necessary.
     /// 
     pub fn spawn(&mut self, name: &str, group: &Group) -> Result<Spawn, SceneError> {
+        let pointer = self.reserve(name, group)?;
+        self.activate(pointer);
+        Ok(self.spawns[pointer].clone())
+    }
+
+    /// Hands out a free slot for a new spawn, without spawning the object yet.
+    /// 
+    fn reserve(&mut self, name: &str, group: &Group) -> Result<Pointer, SceneError> {
 
         if *group >= self.groups.len() {
             return Err(SceneError::GroupNotFound);
         } 
 
-        match self.free.pop() {
+        self.cover_reserved();
+        if self.commands.get_mut().unwrap().free.is_empty() { 
+            self.grow(); 
+        }
+
+        match self.commands.get_mut().unwrap().free.pop() {
             Some(pointer) => {
                 self.spawns[pointer].pointer = pointer;
-                self.spawns[pointer].group = group.clone();
-                self.spawns[pointer].new_name(name);
-                
-                self.in_use.push(self.spawns[pointer].clone());
-                self.groups[*group].push(pointer);
-                self.pool[pointer].replace(self.factories[*group].build(&self.spawns[pointer]));
-
-                Ok(self.spawns[pointer].clone())
+                self.spawns[pointer].group = *group;
+                self.spawns[pointer].generation = self.spawns[pointer].generation.wrapping_add(1);
+                self.spawns[pointer].name = self.intern_name(name);
+                Ok(pointer)
             },
             None => Err(SceneError::Overflow)
         }
     }
 
-    /// Destroy an object. Destroy deactivates an object and therefore stops it from being updated by the core ECS system.
+    /// Spawns the object in a reserved slot, using the factory of its group.
     /// 
-    /// NOTE: Destroy is slow
-    pub fn destroy(&mut self, spawn: &Spawn) {
-        if let Some(u_index) = self.in_use.iter().position(
-            |x| x.pointer == spawn.pointer
-        ) {
-            if let Some(g_index) = self.groups[spawn.group].iter().position(
-                |x| *x == spawn.pointer
-            ) {
-                self.groups[spawn.group].remove(g_index);
+    fn activate(&mut self, pointer: Pointer) {
+        let group = self.spawns[pointer].group;
+
+        self.in_use.insert(pointer, self.spawns[pointer].clone());
+        self.groups[group].insert(pointer, ());
+        self.index_name(pointer);
+
+        match &mut self.columns {
+            Some(storage) => {
+                let object = self.factories[group].build(&self.spawns[pointer]);
+                (storage.scatter)(object, pointer, &mut storage.columns);
+            },
+            None => self.factories[group].rebuild_into(self.pool[pointer].get_mut(), &self.spawns[pointer]),
+        }
+        self.changed[pointer].store(self.tick, Ordering::Relaxed);
+        self.touch_pointer(pointer);
+        self.locate(pointer);
+    }
+
+    /// Same as spawn, but the object is only spawned when the buffered commands are applied.
+    /// The returned Spawn is reserved right away, so it can be stored before the object exists.
+    /// Until then exists will return false for the spawn.
+    /// 
+    /// Only needs a shared scene, so shared systems can spawn as well. When the pool is full, 
+    /// the slots it grows by are reserved right away but only added to the pool when the commands are applied.
+    /// 
+    pub fn defer_spawn(&self, name: &str, group: &Group) -> Result<Spawn, SceneError> {
+        if *group >= self.groups.len() {
+            return Err(SceneError::GroupNotFound);
+        } 
+
+        let mut commands = self.commands.lock().unwrap();
+        if commands.free.is_empty() {
+            let (size, new_size) = (commands.slots, self.grown_size(commands.slots));
+            for pointer in (size..new_size).rev() {
+                commands.free.push(pointer);
             }
+            commands.slots = new_size.max(size);
+        }
+        let pointer = commands.free.pop().ok_or(SceneError::Overflow)?;
+
+        // the slot is only taken over by the spawn once its object is spawned
+        let generation = match self.spawns.get(pointer) {
+            Some(previous) => previous.generation,
+            None => self.retired_generation,
+        };
+        let spawn = Spawn { 
+            pointer, 
+            group: *group, 
+            generation: generation.wrapping_add(1), 
+            name: self.intern_name(name),
+        };
+        commands.reserved.insert(pointer, spawn.clone());
+        commands.push(Command::Spawn(spawn.clone()));
+        Ok(spawn)
+    }
+
+    /// Same as destroy, but the object is only destroyed when the buffered commands are applied.
+    /// 
+    pub fn defer_destroy(&self, spawn: &Spawn) {
+        self.commands.lock().unwrap().push(Command::Destroy(spawn.clone()));
+    }
+
+    /// Buffer a modification of an object, which is applied together with the other buffered commands.
+    /// Modifications of objects that do not exist at that moment are ignored.
+    /// Fails with Columnar when the scene stores its objects in columns, as there is no whole object to modify.
+    /// 
+    pub fn defer_modify<F>(&self, spawn: &Spawn, modify: F) -> Result<(), SceneError>
+        where F: FnOnce(&mut T) + Send + 'static
+    {
+        self.check_pooled()?;
+        self.commands.lock().unwrap().push(Command::Modify(spawn.clone(), Box::new(modify)));
+        Ok(())
+    }
+
+    /// Returns the number of commands waiting to be applied.
+    /// 
+    pub fn pending_commands(&self) -> usize {
+        self.commands.lock().unwrap().len()
+    }
+
+    /// Apply all buffered commands, in the order in which they were added.
+    /// 
+    pub fn apply_commands(&mut self) {
+        self.apply_events();
+        // the Ecs applies the commands after every system, most of the time there are none
+        if !self.commands.get_mut().unwrap().is_empty() { 
+            self.apply_queue(); 
+        }
+    }
 
-            self.in_use.remove(u_index);
-            self.free.push(spawn.pointer)
+    fn apply_queue(&mut self) {
+        self.cover_reserved();
+        let mut queue = self.commands.get_mut().unwrap().take();
+
+        for command in queue.drain(..) {
+            match command {
+                Command::Spawn(spawn) => {
+                    let reserved = &mut self.commands.get_mut().unwrap().reserved;
+                    if reserved.get(spawn.pointer) == Some(&spawn) {
+                        reserved.remove(spawn.pointer);
+                        self.spawns[spawn.pointer] = spawn.clone();
+                        self.activate(spawn.pointer);
+                    }
+                },
+                Command::Destroy(spawn) => self.destroy_now(&spawn),
+                Command::Modify(spawn, modify) => {
+                    // use_columns may have been called after the modification was buffered
+                    if self.exists(&spawn) && self.columns.is_none() {
+                        modify(&mut self.pool[spawn.pointer].borrow_mut());
+                        self.changed[spawn.pointer].store(self.tick, Ordering::Relaxed);
+                        self.touch_pointer(spawn.pointer);
+                        self.locate(spawn.pointer);
+                    }
+                },
+            }
         }
+        self.commands.get_mut().unwrap().recycle(queue);
     }
 
+    /// Destroy an object. Destroy deactivates an object and therefore stops it from being updated by the core ECS system.
+    /// When called by a system during an update, the destroy is deferred until the next sync point,
+    /// so the objects the Ecs is itterating over stay in place. The returned Destroyed tells which of the two happened.
+    /// 
+    /// Spawns reserved by defer_spawn are destroyed once their object has been spawned.
+    /// Returns Missing, without destroying anything, when the spawn is stale or has already been destroyed.
+    /// 
+    /// NOTE: Destroying moves the last spawned object into the place of the destroyed one,
+    /// the order of list_spawned (and of the group lists) is therefore not preserved.
+    pub fn destroy(&mut self, spawn: &Spawn) -> Destroyed {
+        let reserved = self.is_reserved(spawn);
+        if !reserved && !self.exists(spawn) {
+            Destroyed::Missing
+        } else if self.iterating || reserved { 
+            self.defer_destroy(spawn); 
+            Destroyed::Deferred
+        } else {
+            self.destroy_now(spawn);
+            Destroyed::Now
+        }
+    }
+
+    fn destroy_now(&mut self, spawn: &Spawn) {
+        if self.exists(spawn) {
+            self.in_use.remove(spawn.pointer);
+            self.groups[spawn.group].remove(spawn.pointer);
+            self.unindex_name(spawn.pointer);
+            if let Some(spatial) = &mut self.spatial {
+                spatial.index.remove(spawn.pointer);
+            }
+            // columnar scenes drop the components of destroyed objects, so there is nothing to recycle
+            match (&mut self.columns, self.recycle) {
+                (Some(storage), _) => storage.columns.remove_all(spawn.pointer),
+                (None, RecyclePolicy::Keep) => {},
+                (None, RecyclePolicy::Wipe) => *self.pool[spawn.pointer].get_mut() = T::default(),
+                (None, RecyclePolicy::Factory) => self.factories[spawn.group].recycle(self.pool[spawn.pointer].get_mut()),
+            }
+            self.touch_pointer(spawn.pointer);
+            self.commands.get_mut().unwrap().free.push(spawn.pointer)
+        }
+    }
+
+    /// Reset the object at the pointer to its default.
+    /// When the scene stores its objects in columns, the components of a spawned object are replaced 
+    /// by the components of a default object, objects that are not spawned have no components to reset.
+    /// 
     pub fn wipe(&mut self, pointer: &Pointer) {
-        self.pool[*pointer].replace(T::default());
+        match &mut self.columns {
+            Some(storage) if self.in_use.contains(*pointer) => {
+                storage.columns.remove_all(*pointer);
+                (storage.scatter)(T::default(), *pointer, &mut storage.columns);
+            },
+            Some(_) => return,
+            None => { self.pool[*pointer].replace(T::default()); },
+        }
+        self.changed[*pointer].store(self.tick, Ordering::Relaxed);
+        self.touch_pointer(*pointer);
+    }
+
+    /// Store objects per component type from now on, in one densely packed column per type,
+    /// instead of storing whole objects in the pool.
+    /// Objects that are already spawned are moved from the pool into the columns. 
+    /// 
+    /// Factories keep building whole objects, which are then split up by the 'scatter' function,
+    /// usually Columnar::into_columns. The pool of whole objects is released, so methodes that borrow 
+    /// whole objects fail (try_get_ref, for_each_pair, defer_modify, the spatial index), panic (get_ref, get_mut) 
+    /// or find nothing (search_components, query and the like).
+    /// Use column, column_mut and component to access the components instead, and insert_component to add them.
+    /// 
+    /// Systems are matched by the signature of the components an object has in the columns (see Columns::signature),
+    /// System::requirements is not called as there is no whole object to look at.
+    /// Inserting or removing a component lets the Ecs match the object again.
+    /// 
+    pub fn use_columns(&mut self, scatter: fn(T, Pointer, &mut Columns)) {
+        if self.columns.is_some() { return; }
+
+        let mut columns = Columns::default();
+        for pointer in self.in_use.pointers() {
+            scatter(self.pool[*pointer].replace(T::default()), *pointer, &mut columns);
+            // lets the Ecs match the object by its columns
+            self.touched.get_mut().unwrap().insert(*pointer, true);
+        }
+        self.pool = Vec::new();
+        self.columns = Some(ColumnStorage { scatter, columns });
+        if let Some(spatial) = &mut self.spatial {
+            spatial.index.clear();
+        }
+    }
+
+    pub fn is_columnar(&self) -> bool {
+        self.columns.is_some()
+    }
+
+    /// The components of a spawned object, as given by Entity::signature, 
+    /// or by the bits its components were stored with when the scene uses columns, see Columns::signature.
+    /// 
+    pub fn signature(&self, spawn: &Spawn) -> Option<Signature> {
+        if !self.exists(spawn) { return None; }
+        match &self.columns {
+            Some(storage) => storage.columns.signature(spawn.pointer),
+            None => self.pool[spawn.pointer].borrow().signature(),
+        }
+    }
+
+    /// Borrow the column holding all components of a type, 
+    /// or None if the scene does not use columns or no object has a component of this type.
+    /// Panics if the column is already borrowed mutably.
+    /// 
+    pub fn column<C: Send + Sync + 'static>(&self) -> Option<Ref<'_, Column<C>>> {
+        self.columns.as_ref()?.columns.column::<C>()
+    }
+
+    /// Same as column, but borrows the column mutably.
+    /// Panics if the column is already borrowed.
+    /// 
+    pub fn column_mut<C: Send + Sync + 'static>(&self) -> Option<RefMut<'_, Column<C>>> {
+        self.columns.as_ref()?.columns.column_mut::<C>()
+    }
+
+    /// Borrow a single component of a spawned object, when the scene uses columns.
+    /// 
+    pub fn component<C: Send + Sync + 'static>(&self, spawn: &Spawn) -> Option<Ref<'_, C>> {
+        if !self.exists(spawn) { return None; }
+        Ref::filter_map(self.column::<C>()?, |column| column.get(spawn.pointer)).ok()
+    }
+
+    /// Same as component, but borrows the component mutably.
+    /// 
+    pub fn component_mut<C: Send + Sync + 'static>(&self, spawn: &Spawn) -> Option<RefMut<'_, C>> {
+        if !self.exists(spawn) { return None; }
+        RefMut::filter_map(self.column_mut::<C>()?, |column| column.get_mut(spawn.pointer))
+    }
+
+    /// Add a component to a spawned object, when the scene uses columns.
+    /// The column is created if no object had a component of this type before,
+    /// if the object already has a component of this type it is replaced and returned.
+    /// 
+    pub fn insert_component<C: Send + Sync + 'static>(&mut self, spawn: &Spawn, component: C) -> Result<Option<C>, SceneError> {
+        self.check_spawned(spawn)?;
+        let storage = self.columns.as_mut().ok_or(SceneError::NoColumns)?;
+        let replaced = storage.columns.insert(spawn.pointer, component);
+        self.changed[spawn.pointer].store(self.tick, Ordering::Relaxed);
+        // the signature of the object may have changed
+        self.touch_pointer(spawn.pointer);
+        Ok(replaced)
+    }
+
+    /// Remove a component from a spawned object, when the scene uses columns.
+    /// Returns the removed component, or None if the object had no component of this type.
+    /// 
+    pub fn remove_component<C: Send + Sync + 'static>(&mut self, spawn: &Spawn) -> Result<Option<C>, SceneError> {
+        self.check_spawned(spawn)?;
+        let storage = self.columns.as_mut().ok_or(SceneError::NoColumns)?;
+        let removed = storage.columns.remove::<C>(spawn.pointer);
+        self.changed[spawn.pointer].store(self.tick, Ordering::Relaxed);
+        self.touch_pointer(spawn.pointer);
+        Ok(removed)
+    }
+
+    /// Mark an object as changed, so the Ecs will re-evaluate which systems it matches.
+    /// The Ecs only evaluates the requirements of systems for objects that have been spawned, 
+    /// destroyed, modified with defer_modify or touched, so touch an object after activating 
+    /// or deactivating one of its components.
+    /// 
+    pub fn touch(&mut self, spawn: &Spawn) {
+        if self.exists(spawn) {
+            self.changed[spawn.pointer].store(self.tick, Ordering::Relaxed);
+            self.touch_pointer(spawn.pointer);
+            self.locate(spawn.pointer);
+        }
+    }
+
+    /// The current tick of the scene, changes made now are stamped with this tick.
+    /// 
+    pub fn tick(&self) -> Tick {
+        self.tick
+    }
+
+    /// Move on to the next tick, and return it. The Ecs does this every time it runs a system,
+    /// so changes made by one system can be told apart from changes made by another.
+    /// 
+    pub fn advance_tick(&mut self) -> Tick {
+        self.tick += 1;
+        self.tick
+    }
+
+    /// Returns the tick at which a spawned object was last changed, or None if the object does not exist.
+    /// 
+    /// Objects count as changed when they are spawned, touched, modified with defer_modify or visited by for_each_pair,
+    /// and when they are accessed mutably through get_mut, try_get_mut or the mutable queries.
+    /// Changes to component columns are not tracked, except for inserting and removing components.
+    /// 
+    pub fn changed_at(&self, spawn: &Spawn) -> Option<Tick> {
+        match self.exists(spawn) {
+            true => Some(self.changed[spawn.pointer].load(Ordering::Relaxed)),
+            false => None,
+        }
+    }
+
+    /// Returns true if the object exists and has been changed after the tick.
+    /// 
+    pub fn is_changed_since(&self, spawn: &Spawn, tick: Tick) -> bool {
+        self.changed_at(spawn).is_some_and(|changed| changed > tick)
+    }
+
+    /// Itterates over all spawned objects that have been changed after the tick, see changed_at.
+    /// 
+    pub fn changed_since(&self, tick: Tick) -> impl Iterator<Item = &Spawn> + '_ {
+        self.in_use.values().iter().filter(move |spawn| self.changed[spawn.pointer].load(Ordering::Relaxed) > tick)
+    }
+
+    /// Send an event, which can be read by every system (or anyone else) holding an EventReader for its type.
+    /// Events can be read during the frame they are sent in and during the next frame, see update_events.
+    /// 
+    /// Only needs a shared scene, so shared systems can send events as well. Sent events become readable 
+    /// once they are applied, see apply_events, the Ecs does this after every system.
+    /// 
+    pub fn send<Ev: Send + Sync + 'static>(&self, event: Ev) {
+        self.commands.lock().unwrap().events.push(event);
+    }
+
+    /// Make the events that have been sent since the last apply readable.
+    /// Also done by apply_commands and update_events.
+    /// 
+    pub fn apply_events(&mut self) {
+        self.commands.get_mut().unwrap().events.flush_into(&mut self.events);
+    }
+
+    /// Returns the events of a type that the reader has not read yet, oldest first.
+    /// The reader only moves past the events that are itterated over, see Events::read.
+    /// 
+    pub fn read<'a, Ev: Send + Sync + 'static>(&'a self, reader: &'a mut EventReader<Ev>) -> ReadEvents<'a, Ev> {
+        ReadEvents::new(self.events.get::<Ev>(), reader)
+    }
+
+    /// Returns the queue of an event type, or None if no event of this type was ever sent.
+    /// 
+    pub fn events<Ev: Send + Sync + 'static>(&self) -> Option<&Events<Ev>> {
+        self.events.get::<Ev>()
+    }
+
+    /// Drop the events of the previous frame, and keep the events of this frame for one more frame.
+    /// The Ecs calls this at the end of every frame.
+    /// 
+    pub fn update_events(&mut self) {
+        self.apply_events();
+        self.events.swap_all();
+    }
+
+    /// The time passed, as set by the Ecs before it updates the systems.
+    /// 
+    pub fn time(&self) -> &Time {
+        &self.time
+    }
+
+    pub(crate) fn time_mut(&mut self) -> &mut Time {
+        &mut self.time
+    }
+
+    /// Add a global resource, replacing and returning the resource of the same type if there was one.
+    /// 
+    pub fn insert_resource<R: Send + Sync + 'static>(&mut self, resource: R) -> Option<R> {
+        self.resources.insert(resource)
+    }
+
+    pub fn remove_resource<R: Send + Sync + 'static>(&mut self) -> Option<R> {
+        self.resources.remove::<R>()
+    }
+
+    pub fn has_resource<R: Send + Sync + 'static>(&self) -> bool {
+        self.resources.contains::<R>()
+    }
+
+    /// Borrow a global resource, fails with ResourceNotFound if there is no resource of the type,
+    /// or with AlreadyBorrowed if the resource is currently borrowed mutably.
+    /// 
+    pub fn resource<R: Send + Sync + 'static>(&self) -> Result<Ref<'_, R>, SceneError> {
+        self.resources.get::<R>()
+    }
+
+    /// Same as resource, but borrows the resource mutably.
+    /// Fails with AlreadyBorrowed if the resource is currently borrowed in any way.
+    /// 
+    pub fn resource_mut<R: Send + Sync + 'static>(&self) -> Result<RefMut<'_, R>, SceneError> {
+        self.resources.get_mut::<R>()
+    }
+
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    pub(crate) fn set_resources(&mut self, resources: Resources) {
+        self.resources = resources;
+    }
+
+    /// Set a spatial index, that keeps track of where objects are positioned, 
+    /// so objects near a position can be found quickly.
+    /// The 'locate' function tells the index where an object is positioned.
+    /// 
+    /// Objects are added to and removed from the index when they are spawned and destroyed,
+    /// and moved when they are touched, modified with defer_modify, or when update_spatial_index is called.
+    /// The Ecs also moves the objects that have been changed (see changed_since) before every system and at its sync points.
+    /// 
+    pub fn set_spatial_index<I>(&mut self, locate: fn(&T) -> Point, index: I)
+        where I: SpatialIndex + 'static
+    {
+        self.set_spatial(Spatial { locate, index: Box::new(index) });
+    }
+
+    /// The spatial index, if the scene has one and stores whole objects to locate.
+    /// 
+    fn spatial(&self) -> Result<&Spatial<T>, SceneError> {
+        self.check_pooled()?;
+        self.spatial.as_ref().ok_or(SceneError::NoSpatialIndex)
+    }
+
+    pub(crate) fn set_spatial(&mut self, spatial: Spatial<T>) {
+        self.spatial = Some(spatial);
+        self.update_spatial_index();
+    }
+
+    pub fn has_spatial_index(&self) -> bool {
+        self.spatial.is_some()
+    }
+
+    /// Move all spawned objects to their current position in the spatial index.
+    /// Only needed for changes the scene can't see, the Ecs already moves changed objects at its sync points.
+    /// 
+    pub fn update_spatial_index(&mut self) {
+        if let (Some(spatial), None) = (&mut self.spatial, &self.columns) {
+            for pointer in self.in_use.pointers() {
+                spatial.index.insert(*pointer, (spatial.locate)(&self.pool[*pointer].borrow()));
+            }
+        }
+    }
+
+    /// Move the objects that have been touched or changed since the last clear_touched to their current position 
+    /// in the spatial index, so systems can move objects freely. 
+    /// 
+    pub(crate) fn relocate_changed(&mut self) {
+        if let (Some(spatial), None) = (&mut self.spatial, &self.columns) {
+            for pointer in self.touched.get_mut().unwrap().pointers() {
+                if self.in_use.contains(*pointer) {
+                    spatial.index.insert(*pointer, (spatial.locate)(&self.pool[*pointer].borrow()));
+                }
+            }
+        }
+    }
+
+    fn locate(&mut self, pointer: Pointer) {
+        if let (Some(spatial), None) = (&mut self.spatial, &self.columns) {
+            spatial.index.insert(pointer, (spatial.locate)(&self.pool[pointer].borrow()));
+        }
+    }
+
+    /// Returns the spawns of all objects within 'radius' of 'center'.
+    /// 
+    pub fn within_radius(&self, center: Point, radius: f64) -> Result<Vec<Spawn>, SceneError> {
+        let spatial = self.spatial()?;
+        let mut found = Vec::new();
+        spatial.index.within_radius(center, radius, &mut |pointer, _| {
+            found.push(self.spawns[pointer].clone());
+        });
+        Ok(found)
+    }
+
+    /// Returns the spawns of all objects within the rectangle from 'min' to 'max'.
+    /// 
+    pub fn within_rect(&self, min: Point, max: Point) -> Result<Vec<Spawn>, SceneError> {
+        let spatial = self.spatial()?;
+        let mut found = Vec::new();
+        spatial.index.within_rect(min, max, &mut |pointer, _| {
+            found.push(self.spawns[pointer].clone());
+        });
+        Ok(found)
+    }
+
+    /// Returns the spawns of the 'k' objects nearest to 'center', ordered from near to far.
+    /// 
+    pub fn k_nearest(&self, center: Point, k: usize) -> Result<Vec<Spawn>, SceneError> {
+        let spatial = self.spatial()?;
+        Ok(spatial.index.k_nearest(center, k).into_iter()
+            .map(|pointer| self.spawns[pointer].clone())
+            .collect())
+    }
+
+    /// Marks an object that has been spawned, destroyed, touched or modified, 
+    /// so the Ecs will match it to its systems again.
+    /// 
+    fn touch_pointer(&mut self, pointer: Pointer) {
+        self.touched.get_mut().unwrap().insert(pointer, true);
+    }
+
+    pub(crate) fn has_touched(&mut self) -> bool {
+        !self.touched.get_mut().unwrap().is_empty()
+    }
+
+    /// Takes out the objects that have been changed since the last clear_touched, 
+    /// together with whether they have been spawned, destroyed, touched or modified.
+    /// Hand them back with clear_touched, so the allocation can be reused.
+    /// 
+    pub(crate) fn take_touched(&mut self) -> SparseSet<bool> {
+        std::mem::take(self.touched.get_mut().unwrap())
+    }
+
+    pub(crate) fn clear_touched(&mut self, mut touched: SparseSet<bool>) {
+        touched.clear();
+        *self.touched.get_mut().unwrap() = touched;
+    }
+
+    /// Returns the spawn currently using the Pointers slot, if it has been spawned.
+    /// 
+    pub fn spawn_at(&self, pointer: &Pointer) -> Option<&Spawn> {
+        self.in_use.get(*pointer)
     }
 
     /// Checks if the object at the Pointer position has been spawned (is active).
+    /// Stale spawns, of which the slot has been reused by a newer spawn, never exist.
     /// 
     pub fn exists(&self, spawn: &Spawn) -> bool {
-        self.in_use.contains(spawn)
+        self.in_use.get(spawn.pointer) == Some(spawn)
     }
 
     /// Checks if the object with a specific group tag, and Pointer position 
@@ -269,12 +1467,118 @@ impl<T: Entity> Scene<T>  {
     /// will therefore be faster than looping through all spawned objects.
     /// 
     pub fn exists_in_group(&self, spawn: &Spawn, group: Group) -> bool {
-        self.groups[group].contains(spawn.pointer())
+        group < self.groups.len()
+        && self.is_current(spawn)
+        && self.groups[group].contains(spawn.pointer)
+    }
+
+    /// Returns the number of groups, one for each factory.
+    /// 
+    pub fn count_groups(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Itterate over the spawns of all active (spawned) objects in a group, without allocating.
+    /// Itterates over nothing if the group does not exist.
+    /// 
+    pub fn iter_group(&self, group: Group) -> impl Iterator<Item = &Spawn> + '_ {
+        self.group_pointers(group).iter().map(move |pointer| &self.spawns[*pointer])
+    }
+
+    /// Returns the number of active (spawned) objects in a group.
+    /// 
+    pub fn count_in_group(&self, group: Group) -> usize {
+        self.group_pointers(group).len()
+    }
+
+    /// Same as iter_group, but itterates over multiple groups, one group after the other.
+    /// 
+    pub fn spawned_in_groups<'a>(&'a self, groups: &'a [Group]) -> impl Iterator<Item = &'a Spawn> + 'a {
+        groups.iter().flat_map(move |group| self.iter_group(*group))
     }
 
-    /// Returns the maximum capacity of the pool.
+    /// Destroy all active (spawned) objects in a group.
+    /// When called by a system during an update, the destroys are deferred until the next sync point, see destroy. 
+    /// Returns Missing when the group does not exist.
+    /// 
+    pub fn destroy_group(&mut self, group: Group) -> Destroyed {
+        if group >= self.groups.len() {
+            Destroyed::Missing
+        } else if self.iterating {
+            for pointer in self.groups[group].pointers() {
+                self.commands.get_mut().unwrap().push(Command::Destroy(self.spawns[*pointer].clone()));
+            }
+            Destroyed::Deferred
+        } else {
+            while let Some(pointer) = self.groups[group].pointers().last() {
+                let spawn = self.spawns[*pointer].clone();
+                self.destroy_now(&spawn);
+            }
+            Destroyed::Now
+        }
+    }
+
+    fn group_pointers(&self, group: Group) -> &[Pointer] {
+        match self.groups.get(group) {
+            Some(pointers) => pointers.pointers(),
+            None => &[],
+        }
+    }
+
+    /// Returns the current capacity of the pool.
+    /// The pool can only hold more objects if it is allowed to grow, see set_growth_policy.
     /// 
     pub fn size(&self) -> usize {
-        self.pool.len()
+        self.spawns.len()
     }
-}
\ No newline at end of file
+}
+
+pub(crate) fn default_threads() -> usize {
+    std::thread::available_parallelism().map(|threads| threads.get()).unwrap_or(1)
+}
+
+/// Same as Scene::for_each_parallel, for when parts of the scene are borrowed mutably.
+/// 
+fn spread(workers: &OnceLock<Workers>, scene_threads: usize, count: usize, threads: usize, f: &(dyn Fn(usize) + Sync)) {
+    if threads.min(count).min(scene_threads) <= 1 {
+        return (0..count).for_each(f);
+    }
+    workers.get_or_init(|| Workers::new(scene_threads)).for_each(count, threads, f);
+}
+
+/// Splits the pool up into separate slices, one for every chunk of sorted Pointers, 
+/// so every chunk can be visited on another thread. Returns the chunk, the Pointer of the first object 
+/// in the slice, and the slice holding the objects of the chunk.
+/// 
+struct PoolChunks<'a, T> {
+    pool: &'a mut [AtomicRefCell<T>],
+    offset: Pointer, // the Pointer of the first object in pool
+    chunks: std::slice::Chunks<'a, Pointer>,
+}
+
+impl<'a, T> Iterator for PoolChunks<'a, T> {
+    type Item = (&'a [Pointer], Pointer, &'a mut [AtomicRefCell<T>]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.chunks.next()?;
+        let end = chunk[chunk.len() - 1] + 1 - self.offset;
+        let (objects, rest) = std::mem::take(&mut self.pool).split_at_mut(end);
+        self.pool = rest;
+        let offset = self.offset;
+        self.offset += end;
+        Some((chunk, offset, objects))
+    }
+}
+
+/// Returns mutable references to two different objects in the pool.
+/// 
+fn pair_mut<T>(pool: &mut [AtomicRefCell<T>], a: Pointer, b: Pointer) -> (&mut T, &mut T) {
+    assert!(a != b, "an object can't be paired with itself");
+    if a < b {
+        let (left, right) = pool.split_at_mut(b);
+        (left[a].get_mut(), right[0].get_mut())
+    } else {
+        let (left, right) = pool.split_at_mut(a);
+        (right[0].get_mut(), left[b].get_mut())
+    }
+}
@@ -0,0 +1,215 @@
+use std::any::{ type_name, TypeId };
+use std::fmt;
+use std::ops::Range;
+
+use crate::access::Access;
+use crate::criteria::RunCriteria;
+
+
+/// Stage is a phase of a frame, the Ecs updates all systems of a stage before moving on to the next one.
+/// Startup systems are only updated once, at the start of the first frame.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Stage {
+    Startup,
+    PreUpdate,
+    #[default]
+    Update,
+    PostUpdate,
+    Cleanup,
+}
+
+/// SystemOrder tells the Ecs in which stage to update a system, which other systems it has to be updated before or after,
+/// and in which frames to update it, see EcsBuilder::register_system_in.
+///
+/// Systems are referred to by their type, a constraint on a type applies to all systems of that type.
+/// Constraints on systems that are not registered are ignored.
+///
+/// ```ignore
+/// builder.register_system_in(DamageSystem, SystemOrder::new(Stage::Update).after::<AttackSystem>())
+/// ```
+///
+#[derive(Debug, Clone, Default)]
+pub struct SystemOrder {
+    stage: Stage,
+    before: Vec<TypeId>,
+    after: Vec<TypeId>,
+    pub(crate) criteria: RunCriteria,
+}
+
+impl SystemOrder {
+
+    pub fn new(stage: Stage) -> Self {
+        SystemOrder { stage, before: Vec::new(), after: Vec::new(), criteria: RunCriteria::default() }
+    }
+
+    /// Update the system before all systems of type S.
+    ///
+    pub fn before<S: 'static>(mut self) -> Self {
+        self.before.push(TypeId::of::<S>());
+        self
+    }
+
+    /// Update the system after all systems of type S.
+    ///
+    pub fn after<S: 'static>(mut self) -> Self {
+        self.after.push(TypeId::of::<S>());
+        self
+    }
+
+    /// Only update the system in the frames that meet the criteria.
+    ///
+    pub fn run_if(mut self, criteria: RunCriteria) -> Self {
+        self.criteria = criteria;
+        self
+    }
+
+    pub fn stage(&self) -> Stage { self.stage }
+}
+
+/// SystemId identifies a system registered to an Ecs, see EcsBuilder::register_system_with_id and Ecs::system_id.
+/// The index is the order in which the system was registered.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SystemId(pub(crate) usize);
+
+impl SystemId {
+    pub fn index(&self) -> usize { self.0 }
+}
+
+/// ScheduleError tells why the systems of an Ecs could not be put in order.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleError {
+    Cycle { stage: Stage, systems: Vec<&'static str> }, // The systems have to be updated before one another.
+    StageConflict { system: &'static str, other: &'static str }, // The system has to run before a system in an earlier stage, or after one in a later stage.
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleError::Cycle { stage, systems } =>
+                write!(f, "systems in stage {:?} have to run before one another: {}", stage, systems.join(", ")),
+            ScheduleError::StageConflict { system, other } =>
+                write!(f, "system {} is ordered against {}, which runs in a stage that contradicts the order", system, other),
+        }
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+/// A registered system, as far as the scheduler is concerned.
+///
+pub(crate) struct Entry {
+    pub(crate) type_id: TypeId,
+    pub(crate) name: &'static str,
+    pub(crate) order: SystemOrder,
+}
+
+impl Entry {
+    pub(crate) fn of<S: 'static>(order: SystemOrder) -> Self {
+        Entry { type_id: TypeId::of::<S>(), name: type_name::<S>(), order }
+    }
+
+    fn is_ordered_against(&self, other: &Entry) -> bool {
+        self.order.before.contains(&other.type_id) || self.order.after.contains(&other.type_id)
+    }
+}
+
+/// A registered system, as far as running it in parallel is concerned.
+///
+pub(crate) struct Parallel {
+    pub(crate) access: Option<Access>, // None when the system needs exclusive access to the scene
+    pub(crate) only_changed: bool,
+}
+
+/// Puts the systems in the order they have to be updated, returning the indices of the startup systems
+/// and the indices of all other systems.
+/// Within a stage, systems without constraints between them keep the order in which they were registered.
+///
+pub(crate) fn resolve(entries: &[Entry]) -> Result<(Vec<usize>, Vec<usize>), ScheduleError> {
+    // edges[a] holds the systems that have to be updated after system a
+    let mut edges: Vec<Vec<usize>> = vec![Vec::new(); entries.len()];
+
+    for (a, entry) in entries.iter().enumerate() {
+        for (b, other) in entries.iter().enumerate() {
+            if a == b { continue; }
+
+            let before = entry.order.before.contains(&other.type_id);
+            let after = entry.order.after.contains(&other.type_id);
+
+            if (before && entry.order.stage > other.order.stage) || (after && entry.order.stage < other.order.stage) {
+                return Err(ScheduleError::StageConflict { system: entry.name, other: other.name });
+            }
+            if entry.order.stage != other.order.stage { continue; }
+
+            if before { edges[a].push(b); }
+            if after { edges[b].push(a); }
+        }
+    }
+
+    let mut startup = Vec::new();
+    let mut schedule = Vec::new();
+
+    for stage in [Stage::Startup, Stage::PreUpdate, Stage::Update, Stage::PostUpdate, Stage::Cleanup] {
+        let members: Vec<usize> = (0..entries.len()).filter(|index| entries[*index].order.stage == stage).collect();
+        let mut incoming = vec![0; entries.len()];
+        for a in &members {
+            for b in &edges[*a] { incoming[*b] += 1; }
+        }
+
+        let order = if stage == Stage::Startup { &mut startup } else { &mut schedule };
+        let mut done = vec![false; entries.len()];
+
+        // repeatedly take the first registered system that has no systems left to wait for
+        for _ in 0..members.len() {
+            let next = members.iter().copied().find(|index| !done[*index] && incoming[*index] == 0);
+            let next = match next {
+                Some(next) => next,
+                None => return Err(ScheduleError::Cycle {
+                    stage,
+                    systems: members.iter().filter(|index| !done[**index]).map(|index| entries[*index].name).collect(),
+                }),
+            };
+            done[next] = true;
+            for b in &edges[next] { incoming[*b] -= 1; }
+            order.push(next);
+        }
+    }
+    Ok((startup, schedule))
+}
+
+/// Splits the ordered systems into batches of consecutive systems that can be updated at the same time,
+/// returned as ranges into the order.
+/// Systems that don't declare their access, that are in different stages, that are ordered against eachother
+/// or that conflict in the data they access are put in separate batches.
+///
+pub(crate) fn batches(order: &[usize], entries: &[Entry], systems: &[Parallel], per_component: bool) -> Vec<Range<usize>> {
+    let fits = |a: usize, b: usize| {
+        let (Some(access), Some(other)) = (&systems[a].access, &systems[b].access) else { return false };
+
+        // changes made by systems in the same batch are stamped with the same tick,
+        // so a system that only looks at changed objects would miss them
+        let misses_changes = (systems[a].only_changed && other.writes_components())
+            || (systems[b].only_changed && access.writes_components());
+
+        entries[a].order.stage == entries[b].order.stage
+            && !entries[a].is_ordered_against(&entries[b])
+            && !entries[b].is_ordered_against(&entries[a])
+            && !access.conflicts_with(other, per_component)
+            && !misses_changes
+    };
+
+    let mut batches: Vec<Range<usize>> = Vec::new();
+    for (position, index) in order.iter().enumerate() {
+        match batches.last_mut() {
+            Some(batch) if order[batch.clone()].iter().all(|member| fits(*member, *index)) => batch.end += 1,
+            _ => batches.push(position..position + 1),
+        }
+    }
+    // the Ecs only hands shared systems to its worker threads, see Ecs::run_batch
+    debug_assert!(batches.iter()
+        .filter(|batch| batch.len() > 1)
+        .all(|batch| order[batch.clone()].iter().all(|index| systems[*index].access.is_some())));
+    batches
+}
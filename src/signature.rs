@@ -0,0 +1,62 @@
+use std::fmt;
+use std::ops::{ BitAnd, BitOr };
+
+
+/// Signature is a bitset of components, with one bit for every component type of an entity.
+/// Entities return the signature of their active components, see Entity::signature,
+/// and systems the signatures of the components they need or avoid, see System::required.
+///
+/// The Entity derive assigns the bits, in the order the components are declared,
+/// and lets its component enum convert into a Signature.
+///
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Signature(u64);
+
+impl Signature {
+
+    pub const EMPTY: Signature = Signature(0);
+
+    pub const fn from_bits(bits: u64) -> Self { Signature(bits) }
+
+    pub const fn bits(&self) -> u64 { self.0 }
+
+    /// Combine a list of components into one signature.
+    ///
+    pub fn of<I>(components: I) -> Self
+        where I: IntoIterator, I::Item: Into<Signature>
+    {
+        components.into_iter().fold(Signature::EMPTY, |signature, component| signature | component.into())
+    }
+
+    /// Returns true if all bits of 'other' are set in this signature.
+    ///
+    pub const fn contains(&self, other: Signature) -> bool { self.0 & other.0 == other.0 }
+
+    /// Returns true if any bit of 'other' is set in this signature.
+    ///
+    pub const fn intersects(&self, other: Signature) -> bool { self.0 & other.0 != 0 }
+
+    /// Returns this signature with the bits of 'other' cleared.
+    ///
+    pub const fn without(&self, other: Signature) -> Signature { Signature(self.0 & !other.0) }
+
+    pub const fn is_empty(&self) -> bool { self.0 == 0 }
+
+    pub const fn len(&self) -> usize { self.0.count_ones() as usize }
+}
+
+impl BitOr for Signature {
+    type Output = Signature;
+    fn bitor(self, other: Signature) -> Signature { Signature(self.0 | other.0) }
+}
+
+impl BitAnd for Signature {
+    type Output = Signature;
+    fn bitand(self, other: Signature) -> Signature { Signature(self.0 & other.0) }
+}
+
+impl fmt::Debug for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Signature({:#b})", self.0)
+    }
+}
@@ -0,0 +1,123 @@
+use crate::scene::Pointer;
+
+
+const VACANT: usize = usize::MAX;
+
+/// SparseSet keeps a densely packed list of values, each belonging to a Pointer.
+/// Next to the dense list it keeps a sparse index, mapping every Pointer to the
+/// position of its value in the dense list. This makes inserting, removing and
+/// looking up values by Pointer constant time, while the values can still be
+/// itterated as a plain slice.
+///
+/// NOTE: Removing swaps the last value into the removed position,
+/// the order of the dense list is therefore not preserved.
+///
+#[derive(Clone)]
+pub struct SparseSet<V> {
+    dense: Vec<V>,
+    pointers: Vec<Pointer>,
+    sparse: Vec<usize>,
+}
+
+impl<V> Default for SparseSet<V> {
+    fn default() -> Self {
+        SparseSet { dense: Vec::new(), pointers: Vec::new(), sparse: Vec::new() }
+    }
+}
+
+impl<V> SparseSet<V> {
+
+    /// Create a new SparseSet, preallocating room for 'capacity' values.
+    ///
+    pub fn with_capacity(capacity: usize) -> Self {
+        SparseSet {
+            dense: Vec::with_capacity(capacity),
+            pointers: Vec::with_capacity(capacity),
+            sparse: vec![VACANT; capacity],
+        }
+    }
+
+    /// Insert a value for the Pointer, replacing and returning the old value if there was one.
+    ///
+    pub fn insert(&mut self, pointer: Pointer, value: V) -> Option<V> {
+        if pointer >= self.sparse.len() {
+            self.sparse.resize(pointer + 1, VACANT);
+        }
+        match self.sparse[pointer] {
+            VACANT => {
+                self.sparse[pointer] = self.dense.len();
+                self.dense.push(value);
+                self.pointers.push(pointer);
+                None
+            },
+            index => Some(std::mem::replace(&mut self.dense[index], value)),
+        }
+    }
+
+    /// Remove and return the value of the Pointer, if there is one.
+    ///
+    pub fn remove(&mut self, pointer: Pointer) -> Option<V> {
+        let index = self.index_of(pointer)?;
+        let last = *self.pointers.last().unwrap();
+
+        self.sparse[last] = index;
+        self.sparse[pointer] = VACANT;
+        self.pointers.swap_remove(index);
+        Some(self.dense.swap_remove(index))
+    }
+
+    /// Returns the position of the Pointers value within the dense list.
+    ///
+    pub fn index_of(&self, pointer: Pointer) -> Option<usize> {
+        match self.sparse.get(pointer) {
+            Some(&VACANT) | None => None,
+            Some(index) => Some(*index),
+        }
+    }
+
+    pub fn contains(&self, pointer: Pointer) -> bool {
+        self.index_of(pointer).is_some()
+    }
+
+    pub fn get(&self, pointer: Pointer) -> Option<&V> {
+        self.index_of(pointer).map(|index| &self.dense[index])
+    }
+
+    pub fn get_mut(&mut self, pointer: Pointer) -> Option<&mut V> {
+        self.index_of(pointer).map(move |index| &mut self.dense[index])
+    }
+
+    /// All values, densely packed.
+    ///
+    pub fn values(&self) -> &[V] { &self.dense }
+
+    pub fn values_mut(&mut self) -> &mut [V] { &mut self.dense }
+
+    /// Same as pointers() and values_mut() together.
+    ///
+    pub fn split_mut(&mut self) -> (&[Pointer], &mut [V]) { (&self.pointers, &mut self.dense) }
+
+    /// The Pointers belonging to the values, in the same order as values().
+    ///
+    pub fn pointers(&self) -> &[Pointer] { &self.pointers }
+
+    pub fn len(&self) -> usize { self.dense.len() }
+
+    pub fn is_empty(&self) -> bool { self.dense.is_empty() }
+
+    /// Drops the vacant end of the sparse index and releases unused capacity.
+    ///
+    pub fn shrink_to_fit(&mut self) {
+        let len = self.pointers.iter().max().map_or(0, |pointer| pointer + 1);
+        self.sparse.truncate(len);
+        self.sparse.shrink_to_fit();
+        self.dense.shrink_to_fit();
+        self.pointers.shrink_to_fit();
+    }
+
+    pub fn clear(&mut self) {
+        for pointer in &self.pointers { self.sparse[*pointer] = VACANT; }
+        self.dense.clear();
+        self.pointers.clear();
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use crate::scene::Pointer;
+
+
+/// Position of an object on a 2d plane, as (x, y).
+///
+pub type Point = (f64, f64);
+
+/// SpatialIndex keeps track of the positions of objects in the scene,
+/// so objects near a position can be found without checking every object.
+///
+/// The Scene keeps the index up to date, see Scene::set_spatial_index.
+///
+pub trait SpatialIndex: Send + Sync {
+    /// Add an object to the index, or move it if the index already holds the object.
+    fn insert(&mut self, pointer: Pointer, position: Point);
+    fn remove(&mut self, pointer: Pointer);
+    fn clear(&mut self);
+
+    /// Returns the position of an object, as it was last inserted.
+    fn position(&self, pointer: Pointer) -> Option<Point>;
+
+    /// Calls 'found' for every object within the rectangle from 'min' to 'max' (inclusive).
+    fn within_rect(&self, min: Point, max: Point, found: &mut dyn FnMut(Pointer, Point));
+
+    /// Calls 'found' for every object within 'radius' of 'center' (inclusive).
+    fn within_radius(&self, center: Point, radius: f64, found: &mut dyn FnMut(Pointer, Point)) {
+        let min = (center.0 - radius, center.1 - radius);
+        let max = (center.0 + radius, center.1 + radius);
+
+        self.within_rect(min, max, &mut |pointer, position| {
+            if distance_squared(center, position) <= radius * radius {
+                found(pointer, position);
+            }
+        });
+    }
+
+    /// Returns up to 'k' objects nearest to 'center', ordered from near to far.
+    fn k_nearest(&self, center: Point, k: usize) -> Vec<Pointer>;
+}
+
+pub fn distance_squared(a: Point, b: Point) -> f64 {
+    let diff_x = b.0 - a.0;
+    let diff_y = b.1 - a.1;
+    (diff_x * diff_x) + (diff_y * diff_y)
+}
+
+
+type Cell = (i64, i64);
+
+#[derive(Clone, Copy)]
+struct Entry {
+    cell: Cell,
+    position: Point,
+}
+
+/// Grid is a SpatialIndex that divides the plane into square cells of equal size.
+/// Each cell holds the objects positioned inside it, only cells holding objects are stored.
+///
+/// Choose a cell size close to the radius that is usually searched with,
+/// for example the range of the weapons in the game.
+///
+pub struct Grid {
+    cell_size: f64,
+    cells: HashMap<Cell, Vec<Pointer>>,
+    entries: Vec<Option<Entry>>,
+}
+
+impl Grid {
+
+    pub fn new(cell_size: f64) -> Self {
+        assert!(cell_size > 0.0, "grid cell size must be larger than zero");
+        Grid { cell_size, cells: HashMap::new(), entries: Vec::new() }
+    }
+
+    pub fn cell_size(&self) -> f64 { self.cell_size }
+
+    fn cell_of(&self, position: Point) -> Cell {
+        (
+            (position.0 / self.cell_size).floor() as i64,
+            (position.1 / self.cell_size).floor() as i64,
+        )
+    }
+
+    fn entry(&self, pointer: Pointer) -> Option<&Entry> {
+        self.entries.get(pointer).and_then(|entry| entry.as_ref())
+    }
+
+    fn remove_from_cell(&mut self, pointer: Pointer, cell: Cell) {
+        if let Some(pointers) = self.cells.get_mut(&cell) {
+            if let Some(index) = pointers.iter().position(|x| *x == pointer) {
+                pointers.swap_remove(index);
+            }
+            if pointers.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+
+    fn visit_cell(&self, cell: Cell, found: &mut dyn FnMut(Pointer, Point)) {
+        if let Some(pointers) = self.cells.get(&cell) {
+            for pointer in pointers {
+                found(*pointer, self.entries[*pointer].unwrap().position);
+            }
+        }
+    }
+
+    /// Visits the cells on the border of the square of cells 'ring' steps away from 'center'.
+    fn visit_ring(center: Cell, ring: i64, visit: &mut dyn FnMut(Cell)) {
+        if ring == 0 {
+            return visit(center);
+        }
+        for x in (center.0 - ring)..=(center.0 + ring) {
+            visit((x, center.1 - ring));
+            visit((x, center.1 + ring));
+        }
+        for y in (center.1 - ring + 1)..(center.1 + ring) {
+            visit((center.0 - ring, y));
+            visit((center.0 + ring, y));
+        }
+    }
+}
+
+impl SpatialIndex for Grid {
+
+    fn insert(&mut self, pointer: Pointer, position: Point) {
+        let cell = self.cell_of(position);
+
+        if pointer >= self.entries.len() {
+            self.entries.resize(pointer + 1, None);
+        }
+        match self.entries[pointer] {
+            Some(entry) if entry.cell == cell => (),
+            Some(entry) => {
+                self.remove_from_cell(pointer, entry.cell);
+                self.cells.entry(cell).or_default().push(pointer);
+            },
+            None => self.cells.entry(cell).or_default().push(pointer),
+        }
+        self.entries[pointer] = Some(Entry { cell, position });
+    }
+
+    fn remove(&mut self, pointer: Pointer) {
+        if let Some(entry) = self.entry(pointer).copied() {
+            self.remove_from_cell(pointer, entry.cell);
+            self.entries[pointer] = None;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.cells.clear();
+        self.entries.clear();
+    }
+
+    fn position(&self, pointer: Pointer) -> Option<Point> {
+        self.entry(pointer).map(|entry| entry.position)
+    }
+
+    fn within_rect(&self, min: Point, max: Point, found: &mut dyn FnMut(Pointer, Point)) {
+        let min_cell = self.cell_of(min);
+        let max_cell = self.cell_of(max);
+        let mut check = |pointer: Pointer, position: Point| {
+            if position.0 >= min.0 && position.0 <= max.0
+            && position.1 >= min.1 && position.1 <= max.1 {
+                found(pointer, position);
+            }
+        };
+
+        // for large areas it is faster to check the cells holding objects, than every cell in the area
+        // cells saturate at the ends of i64 for huge or infinite areas, so the width and height can't overflow
+        let width = max_cell.0.saturating_sub(min_cell.0).saturating_add(1);
+        let height = max_cell.1.saturating_sub(min_cell.1).saturating_add(1);
+        let area = width as f64 * height as f64;
+        if area > self.cells.len() as f64 {
+            for (cell, pointers) in &self.cells {
+                if cell.0 < min_cell.0 || cell.0 > max_cell.0 || cell.1 < min_cell.1 || cell.1 > max_cell.1 {
+                    continue;
+                }
+                for pointer in pointers {
+                    check(*pointer, self.entries[*pointer].unwrap().position);
+                }
+            }
+        } else {
+            for x in min_cell.0..=max_cell.0 {
+                for y in min_cell.1..=max_cell.1 {
+                    self.visit_cell((x, y), &mut check);
+                }
+            }
+        }
+    }
+
+    fn k_nearest(&self, center: Point, k: usize) -> Vec<Pointer> {
+        let mut nearest: Vec<(f64, Pointer)> = Vec::new();
+        if k == 0 { return Vec::new(); }
+
+        let center_cell = self.cell_of(center);
+        let mut visited = 0;
+        let mut ring = 0;
+
+        // Search the rings of cells around the center, until the remaining rings are
+        // further away than the k-th nearest object found, or all cells are visited.
+        while visited < self.cells.len() {
+            let width = (2 * ring + 1) as f64;
+            if width * width > 4.0 * self.cells.len() as f64 {
+                // the rings got too large, simply check all objects
+                nearest.clear();
+                for pointers in self.cells.values() {
+                    for pointer in pointers {
+                        let position = self.entries[*pointer].unwrap().position;
+                        nearest.push((distance_squared(center, position), *pointer));
+                    }
+                }
+                break;
+            }
+
+            Grid::visit_ring(center_cell, ring, &mut |cell| {
+                if let Some(pointers) = self.cells.get(&cell) {
+                    visited += 1;
+                    for pointer in pointers {
+                        let position = self.entries[*pointer].unwrap().position;
+                        nearest.push((distance_squared(center, position), *pointer));
+                    }
+                }
+            });
+
+            if nearest.len() >= k {
+                sort_nearest(&mut nearest, k);
+
+                let reach = ring as f64 * self.cell_size;
+                if nearest[k - 1].0 <= reach * reach { break; }
+            }
+            ring += 1;
+        }
+
+        sort_nearest(&mut nearest, k);
+        nearest.into_iter().map(|(_, pointer)| pointer).collect()
+    }
+}
+
+fn sort_nearest(nearest: &mut Vec<(f64, Pointer)>, k: usize) {
+    nearest.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    nearest.truncate(k);
+}
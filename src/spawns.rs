@@ -1,61 +1,68 @@
 
+use std::sync::Arc;
+
 use crate::scene::Pointer;
 
 
 pub type Group = usize;
 
+/// Generation is a counter kept for every slot in the pool, it is raised each time
+/// a slot is handed out by spawn. A Spawn carries the generation of the slot it was
+/// created for, so a Spawn that outlived its object can never be mistaken for
+/// the new object that was spawned into the same slot.
+/// 
+pub type Generation = u32;
+
+
+/// Name is the name given to a spawn. Names can be of any length, 
+/// and spawns sharing the same name share the same string.
+/// 
+#[derive(Clone, Default)]
+pub struct Name(Arc<str>);
+
+impl Name {
+    pub fn as_str(&self) -> &str { &self.0 }
 
-#[derive(Clone)]
-pub struct Name([u8; 16]);
+    pub(crate) fn from_shared(name: Arc<str>) -> Self { Name(name) }
+    pub(crate) fn shared(&self) -> Arc<str> { self.0.clone() }
+}
+
+impl From<&str> for Name {
+    fn from(name: &str) -> Self { Name(Arc::from(name)) }
+}
 
 impl PartialEq for Name {
     fn eq(&self, other: &Self) -> bool {
-        for i in 0..16 { 
-            if i < other.0.len() { 
-                if self.0[i] != other.0[i] { return false; }
-            } else {
-                if self.0[i] != 0 { return false; } 
-                else { return true; }
-            }
-        }
-        true
+        Arc::ptr_eq(&self.0, &other.0) || self.0 == other.0
     }
 }
 
-impl Default for Name {
-    fn default() -> Self { Name([0; 16]) }
-}
-
 
 #[derive(Clone, Default)]
 pub struct Spawn {
     pub(crate) pointer: Pointer,
     pub(crate) group: Group,
-    name: Name,
+    pub(crate) generation: Generation,
+    pub(crate) name: Name,
 }
 
 impl Spawn {
     pub fn pointer(&self) -> &Pointer { &self.pointer }
     pub fn group(&self) -> &Group { &self.group }
+    pub fn generation(&self) -> &Generation { &self.generation }
 
     pub fn name(&self) -> &str { 
-        std::str::from_utf8(&self.name.0).unwrap()
+        self.name.as_str()
     }
 
     pub fn new_name(&mut self, name: &str) {
-        let bytes = name.as_bytes();
-        for i in 0..16 { 
-            if i < name.len() { 
-                self.name.0[i] = bytes[i]; 
-            } else {
-                self.name.0[i] = 0 
-            } 
-        }
+        self.name = Name::from(name);
     }
 }
 
 impl PartialEq for Spawn {
     fn eq(&self, other: &Spawn) -> bool {
         self.pointer == other.pointer
+        && self.generation == other.generation
     }
 }
\ No newline at end of file
@@ -0,0 +1,51 @@
+/// Time tells systems how much time has passed, see Scene::time.
+/// All times are in seconds.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Time {
+    delta: f64,
+    elapsed: f64,
+    frame: u64,
+    alpha: f64,
+}
+
+impl Time {
+
+    /// The time passed since the previous update of the systems,
+    /// with a fixed timestep this is always the length of one step.
+    ///
+    pub fn delta(&self) -> f64 { self.delta }
+
+    /// The total time that has been simulated.
+    ///
+    pub fn elapsed(&self) -> f64 { self.elapsed }
+
+    /// The number of times the systems have been updated.
+    ///
+    pub fn frame(&self) -> u64 { self.frame }
+
+    /// How far the time is between the last step and the next one, from 0.0 up to 1.0.
+    /// Renderers can use it to interpolate between the previous and the current state of an object.
+    /// With a variable timestep the systems are always up to date, and alpha is 1.0.
+    ///
+    pub fn alpha(&self) -> f64 { self.alpha }
+
+    pub(crate) fn advance(&mut self, delta: f64) {
+        self.delta = delta;
+        self.elapsed += delta;
+        self.frame += 1;
+    }
+
+    pub(crate) fn set_alpha(&mut self, alpha: f64) {
+        self.alpha = alpha;
+    }
+}
+
+/// Timestep tells the Ecs how to turn the time passed into updates of its systems.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Timestep {
+    #[default]
+    Variable, // Update the systems once per update call, with the time passed as delta.
+    Fixed { hz: f64, max_steps: u32 }, // Update the systems 'hz' times per second, at most 'max_steps' times per update call.
+}
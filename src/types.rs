@@ -1,18 +1,129 @@
 
-use crate::scene::Scene;
+use crate::scene::{ Scene, Pointer };
 use crate::spawns::*;
+use crate::columns::Columns;
+use crate::signature::Signature;
+use crate::access::Access;
 
-pub trait Factory<E: Entity> {
+/// Factories are Send + Sync because they are part of the scene, 
+/// which is shared with the worker threads while systems are updated in parallel, see Scene::get_factory.
+/// 
+pub trait Factory<E: Entity>: Send + Sync {
     fn init(&mut self, group: Group);
     fn build(&self, spawn: &Spawn) -> E;
+
+    /// Spawn a new object into the place of an old one, called by Scene::spawn instead of build.
+    /// Override it to reuse the allocations of the old object, like the capacity of its Vecs,
+    /// by default the old object is replaced by a newly built one.
+    fn rebuild_into(&self, object: &mut E, spawn: &Spawn) {
+        *object = self.build(spawn);
+    }
+
+    /// Called when an object of the group is destroyed, if the scene uses RecyclePolicy::Factory.
+    /// Override it to release what the object holds on to, by default the object is reset to its default.
+    fn recycle(&self, object: &mut E) {
+        *object = E::default();
+    }
 }
 
 pub trait System<E: Entity> {
-    fn requirements(&self, target: &E) -> bool;
+    /// Called once when the Ecs is built, after the resources have been inserted, 
+    /// to let the system set up whatever it needs before the first update.
+    fn init(&mut self, _scene: &mut Scene<E>) {}
+
+    /// Called when an object starts matching the system, before the system is updated.
+    /// Also called while the system is disabled, see Ecs::set_system_enabled.
+    fn on_enter(&mut self, _spawn: &Spawn, _scene: &mut Scene<E>) {}
+
+    /// Called when an object no longer matches the system, because it changed or has been destroyed.
+    /// A destroyed object no longer exists, see Scene::exists. With RecyclePolicy::Keep its last state can still be read,
+    /// unless its slot has already been reused by a newer spawn, see Scene::is_current.
+    fn on_exit(&mut self, _spawn: &Spawn, _scene: &mut Scene<E>) {}
+
+    /// Called once when the Ecs is dropped or its scene is taken out, in the order the systems were registered.
+    /// Skipped when the Ecs is dropped while the thread is panicking, see Ecs::into_scene.
+    fn shutdown(&mut self, _scene: &mut Scene<E>) {}
+
+    /// Tells if the system should update the target object, 
+    /// for checks that can not be expressed with the required and excluded signatures.
+    /// The Ecs caches the outcome, it is only evaluated again when the object is touched, see Scene::touch.
+    /// Never called for columnar scenes, where objects are only matched by their signature, see Scene::use_columns.
+    fn requirements(&self, _target: &E) -> bool { true }
+
+    /// The components an object must have for the system to update it, see Entity::signature.
+    fn required(&self) -> Signature { Signature::EMPTY }
+
+    /// The components an object may not have for the system to update it.
+    fn excluded(&self) -> Signature { Signature::EMPTY }
+
+    /// Tells if the system updates the target object, by checking its signature and requirements.
+    /// Objects without a signature are only checked against the requirements.
+    fn matches(&self, target: &E) -> bool {
+        let signature_matches = match target.signature() {
+            Some(signature) => self.matches_signature(signature),
+            None => true,
+        };
+        signature_matches && self.requirements(target)
+    }
+
+    /// Tells if an object with these components has all required and none of the excluded components.
+    fn matches_signature(&self, signature: Signature) -> bool {
+        signature.contains(self.required()) && !signature.intersects(self.excluded())
+    }
+
+    /// When true, the system is only given the matching objects that have been changed since it last ran,
+    /// changes made by the system itself excluded, see Scene::changed_since.
+    fn only_changed(&self) -> bool { false }
+
     fn update(&mut self, spawn: &Spawn, scene: &mut Scene<E>);
+
+    /// Called once every frame with all objects matching the requirements, 
+    /// by default it updates the objects one by one.
+    /// Override it to process the objects at once, for example by itterating over component columns.
+    fn update_all(&mut self, matches: &[Spawn], scene: &mut Scene<E>) {
+        for spawn in matches {
+            self.update(spawn, scene);
+        }
+    }
+}
+
+/// SharedSystem is a System that declares the components and resources it reads and writes,
+/// so it only needs shared access to the scene and can be updated at the same time as systems it doesn't conflict with.
+/// Register it with EcsBuilder::register_shared_system, systems registered with register_system always run on their own.
+/// Unlike other systems, shared systems have to be Send, they may be updated on one of the worker threads of the scene.
+/// 
+/// NOTE: When the scene stores whole objects, writing any component conflicts with every other component, 
+/// see Access::conflicts_with. Systems that both write components, like an AI and a movement system, 
+/// only run in parallel when the scene stores its objects in columns.
+/// 
+pub trait SharedSystem<E: Entity>: System<E> + Send {
+
+    /// The components and resources the system reads and writes, see Access.
+    fn access(&self) -> Access;
+
+    /// Called instead of update_all, possibly on another thread.
+    /// The scene is shared, so objects can only be changed through get_mut, column_mut or resource_mut
+    /// within the declared access. Spawns, destroys and modifications are deferred (see Scene::defer_spawn),
+    /// and sent events become readable once the batch is done, see Scene::send.
+    fn update_shared(&mut self, matches: &[Spawn], scene: &Scene<E>);
 }
 
-pub trait Entity: Default + Clone {}
+/// Entities are Default so the scene can fill and reset its pool, see RecyclePolicy,
+/// and Send + Sync because objects are borrowed from the worker threads, see Scene::par_for_each.
+/// Clone has always been required, code that clones objects through the trait keeps working.
+/// 
+pub trait Entity: Default + Clone + Send + Sync {
+    /// The components that are active on this object, or None if the entity does not keep track of them.
+    /// The Entity derive implements this for you.
+    fn signature(&self) -> Option<Signature> { None }
+}
+
+/// Columnar entities can be split up into their components, 
+/// so they can be stored per component type, see Scene::use_columns.
+/// The Columnar derive implements this for you, inserting components with the bits of their signature, see Columns::insert_as.
+pub trait Columnar: Entity {
+    fn into_columns(self, pointer: Pointer, columns: &mut Columns);
+}
 
 pub trait Component: Default { 
     fn set_active(&mut self, activate: bool);
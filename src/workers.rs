@@ -0,0 +1,173 @@
+use std::any::Any;
+use std::panic::{ self, AssertUnwindSafe };
+use std::sync::{ Arc, Condvar, Mutex, MutexGuard };
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::thread::{ self, JoinHandle };
+
+
+type Job = &'static (dyn Fn() + Sync);
+
+struct Run {
+    id: u64,
+    job: Job, // the work of the run, only valid while the run lasts
+    wanted: usize, // number of workers that may still join the run
+    working: usize, // number of workers that are still working on the run
+    panic: Option<Box<dyn Any + Send>>, // the first panic of a worker during the run
+}
+
+struct State {
+    runs: Vec<Run>, // the runs that have not ended yet, runs started from within a job come after the run of that job
+    next: u64, // the id of the next run
+    shutdown: bool,
+}
+
+impl State {
+    fn run(&mut self, id: u64) -> &mut Run {
+        self.runs.iter_mut().find(|run| run.id == id).expect("runs only end when all workers are done with them")
+    }
+}
+
+struct Shared {
+    state: Mutex<State>,
+    start: Condvar, // signals the workers that a run started, or that they have to shut down
+    done: Condvar, // signals the caller of a run that a worker is done
+}
+
+/// Workers is a pool of threads that live as long as the pool,
+/// so work can be spread over threads every frame without starting new ones.
+/// The thread that hands out the work always works along, so a pool of 'n' threads starts 'n - 1' workers.
+///
+/// Handing out work doesn't allocate. Work can also be handed out from within a job, for example by a system
+/// that is updated next to others, the workers that are idle help out and the calling thread does the rest.
+///
+pub struct Workers {
+    shared: Arc<Shared>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl Workers {
+
+    pub fn new(threads: usize) -> Self {
+        let shared = Arc::new(Shared {
+            // every thread can be running a job of its own at the same time
+            state: Mutex::new(State { runs: Vec::with_capacity(threads.max(1)), next: 0, shutdown: false }),
+            start: Condvar::new(),
+            done: Condvar::new(),
+        });
+        let handles = (1..threads.max(1))
+            .map(|_| {
+                let shared = shared.clone();
+                thread::spawn(move || work(&shared))
+            })
+            .collect();
+        Workers { shared, handles }
+    }
+
+    /// The number of threads work is spread over, the calling thread included.
+    ///
+    pub fn threads(&self) -> usize {
+        self.handles.len() + 1
+    }
+
+    /// Calls 'f' once for every index from 0 to 'count', spread over at most 'threads' threads.
+    /// Returns when all calls are done, a panic in any of the calls is passed on to the caller.
+    ///
+    pub fn for_each(&self, count: usize, threads: usize, f: &(dyn Fn(usize) + Sync)) {
+        let next = AtomicUsize::new(0);
+        let job = || loop {
+            let index = next.fetch_add(1, Ordering::Relaxed);
+            if index >= count { break; }
+            f(index);
+        };
+        self.run(threads.min(count).saturating_sub(1), &job);
+    }
+
+    /// Calls 'job' on the calling thread and on at most 'helpers' workers at the same time,
+    /// and returns when all of them are done.
+    ///
+    fn run(&self, helpers: usize, job: &(dyn Fn() + Sync)) {
+        let helpers = helpers.min(self.handles.len());
+        if helpers == 0 {
+            return job();
+        }
+        let id = {
+            let mut state = lock(&self.shared.state);
+            let id = state.next;
+            state.next += 1;
+            // SAFETY: the job is only reachable through the state until the run ends,
+            // and Finish waits for all workers to be done with it before the run ends, also when unwinding.
+            let job = unsafe { std::mem::transmute::<&(dyn Fn() + Sync), Job>(job) };
+            state.runs.push(Run { id, job, wanted: helpers, working: 0, panic: None });
+            id
+        };
+        self.shared.start.notify_all();
+
+        let _finish = Finish(&self.shared, id);
+        job();
+    }
+}
+
+impl Drop for Workers {
+    fn drop(&mut self) {
+        lock(&self.shared.state).shutdown = true;
+        self.shared.start.notify_all();
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Ends a run, once the calling thread is done with its part of the job.
+///
+struct Finish<'a>(&'a Shared, u64);
+
+impl Drop for Finish<'_> {
+    fn drop(&mut self) {
+        let mut state = lock(&self.0.state);
+        // workers that did not join yet are not needed anymore, the job has been done
+        state.run(self.1).wanted = 0;
+        while state.run(self.1).working > 0 {
+            state = self.0.done.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+        let index = state.runs.iter().position(|run| run.id == self.1).unwrap();
+        let panic = state.runs.remove(index).panic;
+        drop(state);
+
+        if let Some(panic) = panic {
+            if !thread::panicking() { panic::resume_unwind(panic); }
+        }
+    }
+}
+
+fn work(shared: &Shared) {
+    loop {
+        let (id, job) = {
+            let mut state = lock(&shared.state);
+            loop {
+                if state.shutdown { return; }
+                // the latest run is joined first, as the runs before it may be waiting for it
+                if let Some(run) = state.runs.iter_mut().rev().find(|run| run.wanted > 0) {
+                    run.wanted -= 1;
+                    run.working += 1;
+                    break (run.id, run.job);
+                }
+                state = shared.start.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner());
+            }
+        };
+        let result = panic::catch_unwind(AssertUnwindSafe(job));
+
+        let mut state = lock(&shared.state);
+        let run = state.run(id);
+        if let Err(panic) = result {
+            run.panic.get_or_insert(panic);
+        }
+        run.working -= 1;
+        if run.working == 0 { shared.done.notify_all(); }
+    }
+}
+
+/// The state is never left inconsistent, as jobs run outside of the lock.
+///
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,6 @@
+// The derives report mistakes in the structs they are used on as compile errors.
+#[test]
+fn derive_errors() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/derive/*.rs");
+}
@@ -0,0 +1,9 @@
+use ecs::Component;
+
+#[derive(Default, Clone, Component)]
+struct Health {
+    active: u8,
+    current_hp: u32,
+}
+
+fn main() {}
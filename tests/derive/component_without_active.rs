@@ -0,0 +1,8 @@
+use ecs::Component;
+
+#[derive(Default, Clone, Component)]
+struct Health {
+    current_hp: u32,
+}
+
+fn main() {}
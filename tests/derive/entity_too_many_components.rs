@@ -0,0 +1,73 @@
+use ecs::Entity;
+use ecs::example::components::Position;
+
+#[derive(Default, Clone, Entity)]
+struct Crowded {
+    c0: Position,
+    c1: Position,
+    c2: Position,
+    c3: Position,
+    c4: Position,
+    c5: Position,
+    c6: Position,
+    c7: Position,
+    c8: Position,
+    c9: Position,
+    c10: Position,
+    c11: Position,
+    c12: Position,
+    c13: Position,
+    c14: Position,
+    c15: Position,
+    c16: Position,
+    c17: Position,
+    c18: Position,
+    c19: Position,
+    c20: Position,
+    c21: Position,
+    c22: Position,
+    c23: Position,
+    c24: Position,
+    c25: Position,
+    c26: Position,
+    c27: Position,
+    c28: Position,
+    c29: Position,
+    c30: Position,
+    c31: Position,
+    c32: Position,
+    c33: Position,
+    c34: Position,
+    c35: Position,
+    c36: Position,
+    c37: Position,
+    c38: Position,
+    c39: Position,
+    c40: Position,
+    c41: Position,
+    c42: Position,
+    c43: Position,
+    c44: Position,
+    c45: Position,
+    c46: Position,
+    c47: Position,
+    c48: Position,
+    c49: Position,
+    c50: Position,
+    c51: Position,
+    c52: Position,
+    c53: Position,
+    c54: Position,
+    c55: Position,
+    c56: Position,
+    c57: Position,
+    c58: Position,
+    c59: Position,
+    c60: Position,
+    c61: Position,
+    c62: Position,
+    c63: Position,
+    c64: Position,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use ecs::Entity;
+use ecs::example::components::Position;
+
+#[derive(Default, Clone, Entity)]
+struct Marker(Position);
+
+fn main() {}
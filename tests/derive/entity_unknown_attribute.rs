@@ -0,0 +1,10 @@
+use ecs::Entity;
+use ecs::example::components::Position;
+
+#[derive(Default, Clone, Entity)]
+struct Marker {
+    #[entity(ignore)]
+    position: Position,
+}
+
+fn main() {}